@@ -0,0 +1,28 @@
+-- Tables from before migrations were tracked, so existing databases can adopt this as-is
+CREATE TABLE IF NOT EXISTS "paw-bot".paw_count (
+    user_id int8 NOT NULL,
+    server_id int8 NOT NULL,
+    count int8 NOT NULL,
+    CONSTRAINT paw_count_client_id_server_id_key UNIQUE (user_id, server_id),
+    CONSTRAINT paw_count_count_check CHECK ((count >= 0))
+);
+
+CREATE TABLE IF NOT EXISTS "paw-bot".server_settings (
+    server_id int8 NOT NULL,
+    steal_interval interval NOT NULL,
+    gamble_interval interval NOT NULL,
+    steal_chance int4 NOT NULL,
+    gamble_chance int4 NULL,
+    CONSTRAINT server_settings_gamble_chance_check CHECK (((gamble_chance >= 0) AND (gamble_chance <= 100))),
+    CONSTRAINT server_settings_server_id_key UNIQUE (server_id),
+    CONSTRAINT server_settings_steal_chance_check CHECK (((steal_chance >= 0) AND (steal_chance <= 100)))
+);
+
+CREATE TABLE IF NOT EXISTS "paw-bot".user_limits (
+    user_id int8 NOT NULL,
+    server_id int8 NOT NULL,
+    last_steal timestamptz NULL,
+    last_daily timestamptz NULL,
+    last_gamble timestamptz NULL,
+    CONSTRAINT user_limits_user_id_server_id_key UNIQUE (user_id, server_id)
+);
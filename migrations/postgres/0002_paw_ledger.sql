@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS "paw-bot".paw_ledger (
+    id bigserial NOT NULL,
+    server_id int8 NOT NULL,
+    actor_id int8 NOT NULL,
+    source_id int8 NULL,
+    target_id int8 NULL,
+    amount int8 NOT NULL,
+    reason text NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT paw_ledger_pkey PRIMARY KEY (id),
+    CONSTRAINT paw_ledger_amount_check CHECK ((amount > 0))
+);
+
+CREATE INDEX IF NOT EXISTS paw_ledger_server_id_created_at_idx ON "paw-bot".paw_ledger (server_id, created_at);
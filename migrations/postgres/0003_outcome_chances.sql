@@ -0,0 +1,11 @@
+ALTER TABLE "paw-bot".server_settings
+    ADD COLUMN IF NOT EXISTS gamble_critical_chance int4 NOT NULL DEFAULT 0
+        CONSTRAINT server_settings_gamble_critical_chance_check CHECK (((gamble_critical_chance >= 0) AND (gamble_critical_chance <= 100))),
+    ADD COLUMN IF NOT EXISTS gamble_partial_chance int4 NOT NULL DEFAULT 0
+        CONSTRAINT server_settings_gamble_partial_chance_check CHECK (((gamble_partial_chance >= 0) AND (gamble_partial_chance <= 100))),
+    ADD COLUMN IF NOT EXISTS steal_critical_chance int4 NOT NULL DEFAULT 0
+        CONSTRAINT server_settings_steal_critical_chance_check CHECK (((steal_critical_chance >= 0) AND (steal_critical_chance <= 100))),
+    ADD COLUMN IF NOT EXISTS steal_partial_chance int4 NOT NULL DEFAULT 0
+        CONSTRAINT server_settings_steal_partial_chance_check CHECK (((steal_partial_chance >= 0) AND (steal_partial_chance <= 100))),
+    ADD COLUMN IF NOT EXISTS steal_caught_chance int4 NOT NULL DEFAULT 0
+        CONSTRAINT server_settings_steal_caught_chance_check CHECK (((steal_caught_chance >= 0) AND (steal_caught_chance <= 100)));
@@ -0,0 +1,6 @@
+ALTER TABLE "paw-bot".server_settings
+    ADD COLUMN IF NOT EXISTS ranking_mode text NOT NULL DEFAULT 'standard'
+        CONSTRAINT server_settings_ranking_mode_check CHECK ((ranking_mode IN ('standard', 'dense')));
+
+-- Leaderboards are always read per server, biggest counts first
+CREATE INDEX IF NOT EXISTS paw_count_server_id_count_idx ON "paw-bot".paw_count (server_id, count DESC, user_id);
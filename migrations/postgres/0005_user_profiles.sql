@@ -0,0 +1,8 @@
+-- Discord names and avatars, so leaderboards don't have to look every farmer up
+CREATE TABLE IF NOT EXISTS "paw-bot".user_profiles (
+    user_id bigint NOT NULL,
+    display_name text NOT NULL,
+    avatar_url text NULL,
+    updated_at timestamptz NOT NULL,
+    CONSTRAINT user_profiles_pkey PRIMARY KEY (user_id)
+);
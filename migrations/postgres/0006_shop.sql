@@ -0,0 +1,26 @@
+-- Items each server sells for paws
+CREATE TABLE IF NOT EXISTS "paw-bot".shop_items (
+    id bigserial NOT NULL,
+    server_id int8 NOT NULL,
+    name text NOT NULL,
+    description text NOT NULL DEFAULT '',
+    price int8 NOT NULL,
+    effect text NOT NULL,
+    strength int4 NOT NULL DEFAULT 0,
+    CONSTRAINT shop_items_pkey PRIMARY KEY (id),
+    CONSTRAINT shop_items_server_id_name_key UNIQUE (server_id, name),
+    CONSTRAINT shop_items_price_check CHECK ((price > 0)),
+    CONSTRAINT shop_items_effect_check CHECK ((effect IN ('steal_shield', 'gamble_charm'))),
+    CONSTRAINT shop_items_strength_check CHECK ((strength >= 0 AND strength <= 100))
+);
+
+-- Items bought and not used up yet, removed along with the shop item
+CREATE TABLE IF NOT EXISTS "paw-bot".user_items (
+    user_id int8 NOT NULL,
+    server_id int8 NOT NULL,
+    item_id int8 NOT NULL,
+    quantity int8 NOT NULL,
+    CONSTRAINT user_items_pkey PRIMARY KEY (user_id, server_id, item_id),
+    CONSTRAINT user_items_item_id_fkey FOREIGN KEY (item_id) REFERENCES "paw-bot".shop_items (id) ON DELETE CASCADE,
+    CONSTRAINT user_items_quantity_check CHECK ((quantity > 0))
+);
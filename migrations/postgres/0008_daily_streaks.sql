@@ -0,0 +1,17 @@
+ALTER TABLE "paw-bot".user_limits
+    ADD COLUMN IF NOT EXISTS daily_streak int4 NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS best_daily_streak int4 NOT NULL DEFAULT 0;
+
+-- Anyone who has claimed before starts on a one day streak
+UPDATE "paw-bot".user_limits SET daily_streak = 1, best_daily_streak = 1
+WHERE last_daily IS NOT NULL AND daily_streak = 0;
+
+-- The defaults keep the old flat reward of one paw a day
+ALTER TABLE "paw-bot".server_settings
+    ADD COLUMN IF NOT EXISTS daily_reward int4 NOT NULL DEFAULT 1
+        CONSTRAINT server_settings_daily_reward_check CHECK (((daily_reward >= 1) AND (daily_reward <= 1000))),
+    ADD COLUMN IF NOT EXISTS daily_streak_bonus int4 NOT NULL DEFAULT 0
+        CONSTRAINT server_settings_daily_streak_bonus_check CHECK (((daily_streak_bonus >= 0) AND (daily_streak_bonus <= 1000))),
+    ADD COLUMN IF NOT EXISTS daily_streak_cap int4 NOT NULL DEFAULT 7
+        CONSTRAINT server_settings_daily_streak_cap_check CHECK (((daily_streak_cap >= 1) AND (daily_streak_cap <= 365))),
+    ADD COLUMN IF NOT EXISTS daily_grace interval NOT NULL DEFAULT '1 day';
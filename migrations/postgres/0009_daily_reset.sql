@@ -0,0 +1,5 @@
+ALTER TABLE "paw-bot".server_settings
+    ADD COLUMN IF NOT EXISTS daily_reset text NOT NULL DEFAULT 'rolling'
+        CONSTRAINT server_settings_daily_reset_check CHECK ((daily_reset IN ('rolling', 'calendar'))),
+    -- An IANA name like Europe/Berlin, checked by the bot since postgres can't list them portably
+    ADD COLUMN IF NOT EXISTS timezone text NOT NULL DEFAULT 'UTC';
@@ -0,0 +1,3 @@
+ALTER TABLE "paw-bot".server_settings
+    ADD COLUMN IF NOT EXISTS duration_style text NOT NULL DEFAULT 'compound'
+        CONSTRAINT server_settings_duration_style_check CHECK ((duration_style IN ('compound', 'relative')));
@@ -0,0 +1,11 @@
+-- The defaults keep the old limits of 1 to 10 paws paid out at even money.
+-- NULL percentages are off.
+ALTER TABLE "paw-bot".server_settings
+    ADD COLUMN IF NOT EXISTS gamble_min_stake int4 NOT NULL DEFAULT 1
+        CONSTRAINT server_settings_gamble_min_stake_check CHECK ((gamble_min_stake >= 1)),
+    ADD COLUMN IF NOT EXISTS gamble_max_stake int4 NOT NULL DEFAULT 10
+        CONSTRAINT server_settings_gamble_max_stake_check CHECK ((gamble_max_stake >= 1)),
+    ADD COLUMN IF NOT EXISTS gamble_max_stake_percent int4 NULL
+        CONSTRAINT server_settings_gamble_max_stake_percent_check CHECK (((gamble_max_stake_percent >= 1) AND (gamble_max_stake_percent <= 100))),
+    ADD COLUMN IF NOT EXISTS gamble_payout_percent int4 NULL
+        CONSTRAINT server_settings_gamble_payout_percent_check CHECK (((gamble_payout_percent >= 1) AND (gamble_payout_percent <= 500)));
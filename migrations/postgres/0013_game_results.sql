@@ -0,0 +1,16 @@
+-- Every finished game, so the house edge of each can be checked against what was
+-- actually played. payout is what the player won, negative for losses.
+CREATE TABLE IF NOT EXISTS "paw-bot".game_results (
+    id bigserial NOT NULL,
+    server_id int8 NOT NULL,
+    user_id int8 NOT NULL,
+    game text NOT NULL,
+    stake int8 NOT NULL,
+    payout int8 NOT NULL,
+    played_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT game_results_pkey PRIMARY KEY (id),
+    CONSTRAINT game_results_game_check CHECK ((game IN ('gamble', 'coinflip', 'dice', 'slots', 'blackjack'))),
+    CONSTRAINT game_results_stake_check CHECK ((stake > 0))
+);
+
+CREATE INDEX IF NOT EXISTS game_results_server_id_game_idx ON "paw-bot".game_results (server_id, game);
@@ -0,0 +1,15 @@
+-- Duels waiting for the target to answer. The house holds the challenger's stake
+-- for as long as the row exists.
+CREATE TABLE IF NOT EXISTS "paw-bot".duels (
+    id bigserial NOT NULL,
+    server_id int8 NOT NULL,
+    challenger_id int8 NOT NULL,
+    target_id int8 NOT NULL,
+    stake int8 NOT NULL,
+    expires_at timestamptz NOT NULL,
+    CONSTRAINT duels_pkey PRIMARY KEY (id),
+    CONSTRAINT duels_stake_check CHECK ((stake > 0)),
+    CONSTRAINT duels_target_check CHECK ((challenger_id <> target_id))
+);
+
+CREATE INDEX IF NOT EXISTS duels_expires_at_idx ON "paw-bot".duels (expires_at);
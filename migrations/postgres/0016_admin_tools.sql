@@ -0,0 +1,32 @@
+-- Everything moderators did with /paw admin
+CREATE TABLE IF NOT EXISTS "paw-bot".admin_audit_log (
+    id bigserial NOT NULL,
+    server_id int8 NOT NULL,
+    actor_id int8 NOT NULL,
+    action text NOT NULL,
+    target_id int8 NULL,
+    amount int8 NULL,
+    details text NOT NULL DEFAULT '',
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT admin_audit_log_pkey PRIMARY KEY (id),
+    CONSTRAINT admin_audit_log_action_check CHECK ((action IN ('grant', 'revoke', 'set', 'transfer', 'reset')))
+);
+
+CREATE INDEX IF NOT EXISTS admin_audit_log_server_id_id_idx ON "paw-bot".admin_audit_log (server_id, id);
+
+-- Balances as they were right before a guild reset
+CREATE TABLE IF NOT EXISTS "paw-bot".economy_snapshots (
+    id bigserial NOT NULL,
+    server_id int8 NOT NULL,
+    actor_id int8 NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT economy_snapshots_pkey PRIMARY KEY (id)
+);
+
+CREATE TABLE IF NOT EXISTS "paw-bot".economy_snapshot_balances (
+    snapshot_id int8 NOT NULL,
+    user_id int8 NOT NULL,
+    count int8 NOT NULL,
+    CONSTRAINT economy_snapshot_balances_pkey PRIMARY KEY (snapshot_id, user_id),
+    CONSTRAINT economy_snapshot_balances_snapshot_id_fkey FOREIGN KEY (snapshot_id) REFERENCES "paw-bot".economy_snapshots (id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Imports through /paw admin are audited too
+ALTER TABLE "paw-bot".admin_audit_log
+    DROP CONSTRAINT IF EXISTS admin_audit_log_action_check,
+    ADD CONSTRAINT admin_audit_log_action_check CHECK ((action IN ('grant', 'revoke', 'set', 'transfer', 'reset', 'import')));
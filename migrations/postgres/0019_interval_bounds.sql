@@ -0,0 +1,8 @@
+-- Intervals are now kept between a minute and 30 days, so settings saved before
+-- that still read back through /paw config and imports
+UPDATE "paw-bot".server_settings SET steal_interval = '1 minute' WHERE steal_interval < '1 minute';
+UPDATE "paw-bot".server_settings SET steal_interval = '30 days' WHERE steal_interval > '30 days';
+UPDATE "paw-bot".server_settings SET gamble_interval = '1 minute' WHERE gamble_interval < '1 minute';
+UPDATE "paw-bot".server_settings SET gamble_interval = '30 days' WHERE gamble_interval > '30 days';
+UPDATE "paw-bot".server_settings SET daily_grace = '1 minute' WHERE daily_grace < '1 minute';
+UPDATE "paw-bot".server_settings SET daily_grace = '30 days' WHERE daily_grace > '30 days';
@@ -0,0 +1,53 @@
+-- SQLite has no schemas or intervals: tables live in the main database and
+-- intervals are stored as whole seconds
+CREATE TABLE IF NOT EXISTS paw_count (
+    user_id INTEGER NOT NULL,
+    server_id INTEGER NOT NULL,
+    count INTEGER NOT NULL,
+    CONSTRAINT paw_count_client_id_server_id_key UNIQUE (user_id, server_id),
+    CONSTRAINT paw_count_count_check CHECK ((count >= 0))
+);
+
+CREATE TABLE IF NOT EXISTS server_settings (
+    server_id INTEGER NOT NULL,
+    steal_interval INTEGER NOT NULL,
+    gamble_interval INTEGER NOT NULL,
+    steal_chance INTEGER NOT NULL,
+    gamble_chance INTEGER NULL,
+    gamble_critical_chance INTEGER NOT NULL DEFAULT 0,
+    gamble_partial_chance INTEGER NOT NULL DEFAULT 0,
+    steal_critical_chance INTEGER NOT NULL DEFAULT 0,
+    steal_partial_chance INTEGER NOT NULL DEFAULT 0,
+    steal_caught_chance INTEGER NOT NULL DEFAULT 0,
+    CONSTRAINT server_settings_server_id_key UNIQUE (server_id),
+    CONSTRAINT server_settings_steal_chance_check CHECK (((steal_chance >= 0) AND (steal_chance <= 100))),
+    CONSTRAINT server_settings_gamble_chance_check CHECK (((gamble_chance >= 0) AND (gamble_chance <= 100))),
+    CONSTRAINT server_settings_gamble_critical_chance_check CHECK (((gamble_critical_chance >= 0) AND (gamble_critical_chance <= 100))),
+    CONSTRAINT server_settings_gamble_partial_chance_check CHECK (((gamble_partial_chance >= 0) AND (gamble_partial_chance <= 100))),
+    CONSTRAINT server_settings_steal_critical_chance_check CHECK (((steal_critical_chance >= 0) AND (steal_critical_chance <= 100))),
+    CONSTRAINT server_settings_steal_partial_chance_check CHECK (((steal_partial_chance >= 0) AND (steal_partial_chance <= 100))),
+    CONSTRAINT server_settings_steal_caught_chance_check CHECK (((steal_caught_chance >= 0) AND (steal_caught_chance <= 100)))
+);
+
+CREATE TABLE IF NOT EXISTS user_limits (
+    user_id INTEGER NOT NULL,
+    server_id INTEGER NOT NULL,
+    last_steal TEXT NULL,
+    last_daily TEXT NULL,
+    last_gamble TEXT NULL,
+    CONSTRAINT user_limits_user_id_server_id_key UNIQUE (user_id, server_id)
+);
+
+CREATE TABLE IF NOT EXISTS paw_ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    actor_id INTEGER NOT NULL,
+    source_id INTEGER NULL,
+    target_id INTEGER NULL,
+    amount INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    CONSTRAINT paw_ledger_amount_check CHECK ((amount > 0))
+);
+
+CREATE INDEX IF NOT EXISTS paw_ledger_server_id_created_at_idx ON paw_ledger (server_id, created_at);
@@ -0,0 +1,6 @@
+ALTER TABLE server_settings
+    ADD COLUMN ranking_mode TEXT NOT NULL DEFAULT 'standard'
+        CONSTRAINT server_settings_ranking_mode_check CHECK ((ranking_mode IN ('standard', 'dense')));
+
+-- Leaderboards are always read per server, biggest counts first
+CREATE INDEX IF NOT EXISTS paw_count_server_id_count_idx ON paw_count (server_id, count DESC, user_id);
@@ -0,0 +1,8 @@
+-- Discord names and avatars, so leaderboards don't have to look every farmer up
+CREATE TABLE IF NOT EXISTS user_profiles (
+    user_id INTEGER NOT NULL,
+    display_name TEXT NOT NULL,
+    avatar_url TEXT NULL,
+    updated_at TEXT NOT NULL,
+    CONSTRAINT user_profiles_pkey PRIMARY KEY (user_id)
+);
@@ -0,0 +1,26 @@
+-- Items each server sells for paws
+CREATE TABLE IF NOT EXISTS shop_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    price INTEGER NOT NULL,
+    effect TEXT NOT NULL,
+    strength INTEGER NOT NULL DEFAULT 0,
+    CONSTRAINT shop_items_server_id_name_key UNIQUE (server_id, name),
+    CONSTRAINT shop_items_price_check CHECK ((price > 0)),
+    CONSTRAINT shop_items_effect_check CHECK ((effect IN ('steal_shield', 'gamble_charm'))),
+    CONSTRAINT shop_items_strength_check CHECK ((strength >= 0 AND strength <= 100))
+);
+
+-- Items bought and not used up yet, removed along with the shop item
+CREATE TABLE IF NOT EXISTS user_items (
+    user_id INTEGER NOT NULL,
+    server_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    quantity INTEGER NOT NULL,
+    CONSTRAINT user_items_pkey PRIMARY KEY (user_id, server_id, item_id),
+    CONSTRAINT user_items_item_id_fkey FOREIGN KEY (item_id) REFERENCES shop_items (id) ON DELETE CASCADE,
+    -- Using an item takes the count through zero before the row is deleted
+    CONSTRAINT user_items_quantity_check CHECK ((quantity >= 0))
+);
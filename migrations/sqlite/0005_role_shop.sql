@@ -0,0 +1,21 @@
+-- Server roles for sale, duration_days is NULL for roles bought for good
+CREATE TABLE IF NOT EXISTS role_listings (
+    server_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    price INTEGER NOT NULL,
+    duration_days INTEGER NULL,
+    CONSTRAINT role_listings_pkey PRIMARY KEY (server_id, role_id),
+    CONSTRAINT role_listings_price_check CHECK ((price > 0)),
+    CONSTRAINT role_listings_duration_days_check CHECK ((duration_days > 0))
+);
+
+-- Rented roles that still have to be taken away
+CREATE TABLE IF NOT EXISTS role_rentals (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    expires_at TEXT NOT NULL,
+    CONSTRAINT role_rentals_pkey PRIMARY KEY (server_id, user_id, role_id)
+);
+
+CREATE INDEX IF NOT EXISTS role_rentals_expires_at_idx ON role_rentals (expires_at);
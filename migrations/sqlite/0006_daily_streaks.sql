@@ -0,0 +1,15 @@
+ALTER TABLE user_limits ADD COLUMN daily_streak INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE user_limits ADD COLUMN best_daily_streak INTEGER NOT NULL DEFAULT 0;
+
+-- Anyone who has claimed before starts on a one day streak
+UPDATE user_limits SET daily_streak = 1, best_daily_streak = 1
+WHERE last_daily IS NOT NULL AND daily_streak = 0;
+
+-- The defaults keep the old flat reward of one paw a day. Intervals are in seconds.
+ALTER TABLE server_settings ADD COLUMN daily_reward INTEGER NOT NULL DEFAULT 1
+    CONSTRAINT server_settings_daily_reward_check CHECK (((daily_reward >= 1) AND (daily_reward <= 1000)));
+ALTER TABLE server_settings ADD COLUMN daily_streak_bonus INTEGER NOT NULL DEFAULT 0
+    CONSTRAINT server_settings_daily_streak_bonus_check CHECK (((daily_streak_bonus >= 0) AND (daily_streak_bonus <= 1000)));
+ALTER TABLE server_settings ADD COLUMN daily_streak_cap INTEGER NOT NULL DEFAULT 7
+    CONSTRAINT server_settings_daily_streak_cap_check CHECK (((daily_streak_cap >= 1) AND (daily_streak_cap <= 365)));
+ALTER TABLE server_settings ADD COLUMN daily_grace INTEGER NOT NULL DEFAULT 86400;
@@ -0,0 +1,4 @@
+ALTER TABLE server_settings ADD COLUMN daily_reset TEXT NOT NULL DEFAULT 'rolling'
+    CONSTRAINT server_settings_daily_reset_check CHECK ((daily_reset IN ('rolling', 'calendar')));
+-- An IANA name like Europe/Berlin, checked by the bot
+ALTER TABLE server_settings ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
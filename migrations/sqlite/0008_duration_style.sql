@@ -0,0 +1,2 @@
+ALTER TABLE server_settings ADD COLUMN duration_style TEXT NOT NULL DEFAULT 'compound'
+    CONSTRAINT server_settings_duration_style_check CHECK ((duration_style IN ('compound', 'relative')));
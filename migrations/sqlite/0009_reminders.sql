@@ -0,0 +1,15 @@
+-- Members who want to hear when an action is ready again. due_at is set while a
+-- reminder is waiting to go out, channel_id is where channel reminders are posted.
+CREATE TABLE IF NOT EXISTS reminders (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    delivery TEXT NOT NULL,
+    channel_id INTEGER NULL,
+    due_at TEXT NULL,
+    CONSTRAINT reminders_pkey PRIMARY KEY (server_id, user_id, action),
+    CONSTRAINT reminders_action_check CHECK ((action IN ('daily', 'gamble', 'steal'))),
+    CONSTRAINT reminders_delivery_check CHECK (((delivery = 'dm') OR (delivery = 'channel' AND channel_id IS NOT NULL)))
+);
+
+CREATE INDEX IF NOT EXISTS reminders_due_at_idx ON reminders (due_at) WHERE due_at IS NOT NULL;
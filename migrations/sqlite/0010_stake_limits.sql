@@ -0,0 +1,10 @@
+-- The defaults keep the old limits of 1 to 10 paws paid out at even money.
+-- NULL percentages are off.
+ALTER TABLE server_settings ADD COLUMN gamble_min_stake INTEGER NOT NULL DEFAULT 1
+    CONSTRAINT server_settings_gamble_min_stake_check CHECK ((gamble_min_stake >= 1));
+ALTER TABLE server_settings ADD COLUMN gamble_max_stake INTEGER NOT NULL DEFAULT 10
+    CONSTRAINT server_settings_gamble_max_stake_check CHECK ((gamble_max_stake >= 1));
+ALTER TABLE server_settings ADD COLUMN gamble_max_stake_percent INTEGER NULL
+    CONSTRAINT server_settings_gamble_max_stake_percent_check CHECK (((gamble_max_stake_percent >= 1) AND (gamble_max_stake_percent <= 100)));
+ALTER TABLE server_settings ADD COLUMN gamble_payout_percent INTEGER NULL
+    CONSTRAINT server_settings_gamble_payout_percent_check CHECK (((gamble_payout_percent >= 1) AND (gamble_payout_percent <= 500)));
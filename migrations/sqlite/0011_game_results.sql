@@ -0,0 +1,15 @@
+-- Every finished game, so the house edge of each can be checked against what was
+-- actually played. payout is what the player won, negative for losses.
+CREATE TABLE IF NOT EXISTS game_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    game TEXT NOT NULL,
+    stake INTEGER NOT NULL,
+    payout INTEGER NOT NULL,
+    played_at TEXT NOT NULL,
+    CONSTRAINT game_results_game_check CHECK ((game IN ('gamble', 'coinflip', 'dice', 'slots', 'blackjack'))),
+    CONSTRAINT game_results_stake_check CHECK ((stake > 0))
+);
+
+CREATE INDEX IF NOT EXISTS game_results_server_id_game_idx ON game_results (server_id, game);
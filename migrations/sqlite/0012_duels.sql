@@ -0,0 +1,14 @@
+-- Duels waiting for the target to answer. The house holds the challenger's stake
+-- for as long as the row exists.
+CREATE TABLE IF NOT EXISTS duels (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    challenger_id INTEGER NOT NULL,
+    target_id INTEGER NOT NULL,
+    stake INTEGER NOT NULL,
+    expires_at TEXT NOT NULL,
+    CONSTRAINT duels_stake_check CHECK ((stake > 0)),
+    CONSTRAINT duels_target_check CHECK ((challenger_id <> target_id))
+);
+
+CREATE INDEX IF NOT EXISTS duels_expires_at_idx ON duels (expires_at);
@@ -0,0 +1,3 @@
+-- Histories list a member's transfers from either side, newest first
+CREATE INDEX IF NOT EXISTS paw_ledger_server_id_source_id_idx ON paw_ledger (server_id, source_id, id);
+CREATE INDEX IF NOT EXISTS paw_ledger_server_id_target_id_idx ON paw_ledger (server_id, target_id, id);
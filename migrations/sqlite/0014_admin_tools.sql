@@ -0,0 +1,30 @@
+-- Everything moderators did with /paw admin
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    actor_id INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    target_id INTEGER NULL,
+    amount INTEGER NULL,
+    details TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    CONSTRAINT admin_audit_log_action_check CHECK ((action IN ('grant', 'revoke', 'set', 'transfer', 'reset')))
+);
+
+CREATE INDEX IF NOT EXISTS admin_audit_log_server_id_id_idx ON admin_audit_log (server_id, id);
+
+-- Balances as they were right before a guild reset
+CREATE TABLE IF NOT EXISTS economy_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    actor_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS economy_snapshot_balances (
+    snapshot_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    count INTEGER NOT NULL,
+    CONSTRAINT economy_snapshot_balances_pkey PRIMARY KEY (snapshot_id, user_id),
+    CONSTRAINT economy_snapshot_balances_snapshot_id_fkey FOREIGN KEY (snapshot_id) REFERENCES economy_snapshots (id) ON DELETE CASCADE
+);
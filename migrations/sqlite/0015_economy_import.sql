@@ -0,0 +1,21 @@
+-- Imports through /paw admin are audited too. SQLite can't change a CHECK in
+-- place, so the table is copied over.
+CREATE TABLE admin_audit_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    actor_id INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    target_id INTEGER NULL,
+    amount INTEGER NULL,
+    details TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    CONSTRAINT admin_audit_log_action_check CHECK ((action IN ('grant', 'revoke', 'set', 'transfer', 'reset', 'import')))
+);
+
+INSERT INTO admin_audit_log_new (id, server_id, actor_id, action, target_id, amount, details, created_at)
+SELECT id, server_id, actor_id, action, target_id, amount, details, created_at FROM admin_audit_log;
+
+DROP TABLE admin_audit_log;
+ALTER TABLE admin_audit_log_new RENAME TO admin_audit_log;
+
+CREATE INDEX IF NOT EXISTS admin_audit_log_server_id_id_idx ON admin_audit_log (server_id, id);
@@ -0,0 +1,12 @@
+-- Games being played. The house holds the player's stake for as long as the row
+-- exists, a player's games are told apart by when they were placed.
+CREATE TABLE IF NOT EXISTS open_games (
+    server_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    stake INTEGER NOT NULL,
+    placed_at TEXT NOT NULL,
+    CONSTRAINT open_games_pkey PRIMARY KEY (server_id, user_id, placed_at),
+    CONSTRAINT open_games_stake_check CHECK ((stake > 0))
+);
+
+CREATE INDEX IF NOT EXISTS open_games_placed_at_idx ON open_games (placed_at);
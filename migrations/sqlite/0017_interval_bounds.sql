@@ -0,0 +1,5 @@
+-- Intervals are now kept between a minute and 30 days, so settings saved before
+-- that still read back through /paw config and imports
+UPDATE server_settings SET steal_interval = MIN(MAX(steal_interval, 60), 2592000);
+UPDATE server_settings SET gamble_interval = MIN(MAX(gamble_interval, 60), 2592000);
+UPDATE server_settings SET daily_grace = MIN(MAX(daily_grace, 60), 2592000);
@@ -0,0 +1,301 @@
+use std::path::PathBuf;
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use poise::ChoiceParameter;
+use crate::helpers::backup::{describe_import, BackupFormat, EconomyBackup};
+use crate::helpers::config::Config;
+use crate::helpers::settings::{setting_value, SettingKey};
+use crate::helpers::store::{open_store, PawStore};
+use crate::helpers::structs::{AdminAction, AuditEntry, ImportMode, ServerSettings, OPERATOR_ID};
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Farm paws on Discord")]
+pub struct Cli {
+    /// Config file to read, PAW_CONFIG or paw-bot.toml if left out
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Same as the migrate command, kept for older deploy scripts
+    #[arg(long, hide = true)]
+    migrate_only: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>
+}
+
+impl Cli {
+    pub fn config_file(&self) -> Option<&std::path::Path> {
+        self.config.as_deref()
+    }
+
+    // Running the bot is what happens without a command
+    pub fn command(self) -> Command {
+        match (self.command, self.migrate_only) {
+            (Some(command), _) => command,
+            (None, true) => Command::Migrate,
+            (None, false) => Command::Run
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Connect to Discord and run the bot
+    Run,
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Write a server's balances, limits and settings to a file
+    Export {
+        guild_id: u64,
+        /// Where to write, standard output if left out
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// json or csv, going by the output's extension if left out
+        #[arg(short, long)]
+        format: Option<BackupFormat>
+    },
+    /// Load a server's balances, limits and settings from an export
+    Import {
+        guild_id: u64,
+        file: PathBuf,
+        /// merge keeps members missing from the file and is the default, replace drops them
+        #[arg(short, long)]
+        mode: Option<ImportMode>,
+        /// Only check the file and say what it would change
+        #[arg(long)]
+        dry_run: bool,
+        /// json or csv, going by the file's extension if left out
+        #[arg(short, long)]
+        format: Option<BackupFormat>
+    },
+    /// Set a member's balance, through the ledger like /paw admin set
+    SetBalance {
+        guild_id: u64,
+        user_id: u64,
+        count: u64,
+        /// Why, for the audit log
+        #[arg(short, long)]
+        note: Option<String>
+    },
+    /// Print a server's settings
+    ShowSettings {
+        guild_id: u64
+    },
+    /// Check the configuration and the database without starting the bot
+    CheckConfig
+}
+
+// Everything but run. Output is printed rather than logged, so it shows up
+// whatever RUST_LOG says.
+pub async fn run(command: Command, config: &Config) -> Result<(), Error> {
+    let defaults = &config.defaults;
+
+    match command {
+        Command::Run => unreachable!("main starts the bot itself"),
+        Command::CheckConfig => check_config(config).await,
+        Command::Migrate => {
+            let store = open_store(config).await?;
+            let applied = store.migrate().await?;
+            println!("Database schema is up to date ({} {} applied)", applied, if applied != 1 {"migrations"} else {"migration"});
+            Ok(())
+        }
+        Command::Export { guild_id, output, format } => {
+            export(open_current_store(config).await?.as_ref(), guild_id, output, format).await
+        }
+        Command::Import { guild_id, file, mode, dry_run, format } => {
+            import(open_current_store(config).await?.as_ref(), defaults, guild_id, file, mode, dry_run, format).await
+        }
+        Command::SetBalance { guild_id, user_id, count, note } => {
+            set_balance(open_current_store(config).await?.as_ref(), guild_id, user_id, count, note).await
+        }
+        Command::ShowSettings { guild_id } => {
+            show_settings(open_current_store(config).await?.as_ref(), defaults, guild_id).await
+        }
+    }
+}
+
+// Operator commands leave migrating to the migrate command, so pointing a new
+// build at a production database never changes its schema by surprise
+async fn open_current_store(config: &Config) -> Result<std::sync::Arc<dyn PawStore>, Error> {
+    let store = open_store(config).await?;
+    let status = store.migration_status().await?;
+
+    if !status.is_up_to_date() {
+        return Err(format!(
+            "Database schema is at version {} but {} is required, run the migrate command first",
+            status.current_version, status.latest_version
+        ).into());
+    }
+
+    Ok(store)
+}
+
+// Everything in the configuration was checked while loading it, this only adds
+// what can't be known without trying
+async fn check_config(config: &Config) -> Result<(), Error> {
+    let mut problems = Vec::new();
+
+    match &config.file {
+        Some(file) => println!("Config file: {}", file.display()),
+        None => println!("Config file: none, using the environment only")
+    }
+    println!("Log level: {}", config.log_level.as_str().to_ascii_lowercase());
+    println!("Owners: {}", describe_ids(&config.owner_ids, "nobody"));
+    println!("Servers: {}", describe_ids(&config.allowed_guilds, "all of them"));
+
+    let changed = (0..).map_while(SettingKey::from_index)
+        .filter(|key| setting_value(&config.defaults, *key) != setting_value(&ServerSettings::default(), *key))
+        .map(|key| format!("{} = {}", key.name(), setting_value(&config.defaults, key)))
+        .collect::<Vec<String>>();
+    match changed.is_empty() {
+        true => println!("Default settings: built in"),
+        false => println!("Default settings: built in, except {}", changed.join(", "))
+    }
+
+    if config.discord_token.is_none() {
+        problems.push("discord_token isn't set, put it in the config file or set DISCORD_TOKEN".to_string());
+    }
+
+    match open_store(config).await {
+        Ok(store) => match store.migration_status().await {
+            Ok(status) if status.is_up_to_date() => {
+                println!("Database: connected with up to {} connections, schema is up to date", config.pool_size);
+            }
+            Ok(status) => {
+                println!(
+                    "Database: connected with up to {} connections, schema is at version {} and {} {} pending",
+                    config.pool_size,
+                    status.current_version, status.pending.len(), if status.pending.len() != 1 {"migrations are"} else {"migration is"}
+                );
+            }
+            Err(error) => problems.push(format!("Database: {}", error))
+        },
+        Err(error) => problems.push(format!("database_url: couldn't connect, {}", error))
+    }
+
+    if !problems.is_empty() {
+        return Err(problems.join("\n").into());
+    }
+
+    println!("Configuration is fine");
+    Ok(())
+}
+
+fn describe_ids(ids: &[u64], none: &str) -> String {
+    match ids.is_empty() {
+        true => none.to_string(),
+        false => ids.iter().map(u64::to_string).collect::<Vec<String>>().join(", ")
+    }
+}
+
+async fn export(store: &dyn PawStore, guild_id: u64, output: Option<PathBuf>, format: Option<BackupFormat>) -> Result<(), Error> {
+    let format = format
+        .or_else(|| output.as_ref().and_then(|output| BackupFormat::from_file_name(&output.to_string_lossy())))
+        .unwrap_or(BackupFormat::Json);
+    let backup = EconomyBackup::from_data(&store.export_economy(guild_id).await?);
+    let bytes = backup.write(format)?;
+
+    match output {
+        Some(output) => {
+            std::fs::write(&output, bytes)?;
+            println!(
+                "Wrote {} balances and {} cooldown and streak records to {}",
+                backup.paw_count.len(), backup.user_limits.len(), output.display()
+            );
+        }
+        None => {
+            use std::io::Write;
+            std::io::stdout().write_all(&bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn import(
+    store: &dyn PawStore,
+    defaults: &ServerSettings,
+    guild_id: u64,
+    file: PathBuf,
+    mode: Option<ImportMode>,
+    dry_run: bool,
+    format: Option<BackupFormat>
+) -> Result<(), Error> {
+    let chose_mode = mode.is_some();
+    let mode = mode.unwrap_or(ImportMode::Merge);
+    let Some(format) = format.or_else(|| BackupFormat::from_file_name(&file.to_string_lossy())) else {
+        return Err(format!("Can't tell what kind of file {} is, pass --format", file.display()).into());
+    };
+    let backup = EconomyBackup::read(&std::fs::read(&file)?, format)
+        .map_err(|error| format!("Couldn't read {}: {}", file.display(), error))?;
+
+    let current = store.get_server_settings(guild_id).await?.unwrap_or_else(|| defaults.clone());
+    let data = backup.to_data(mode, &current, defaults)
+        .map_err(|problems| format!("{} can't be imported:\n{}", file.display(), problems.join("\n")))?;
+
+    let summary = describe_import(&data, mode);
+    let verb = if mode == ImportMode::Replace {"replace everything with"} else {"merge in"};
+    if dry_run {
+        println!("Nothing was changed. Importing {} would {} {}.", file.display(), verb, summary);
+        if !chose_mode {
+            println!("Merging is the default, pass --mode replace to drop members missing from the file.");
+        }
+        return Ok(());
+    }
+
+    store.import_economy(guild_id, OPERATOR_ID, &data, mode).await?;
+    store.add_audit_entry(&AuditEntry {
+        id: 0,
+        server_id: guild_id,
+        actor_id: OPERATOR_ID,
+        action: AdminAction::Import,
+        target_id: None,
+        amount: Some(data.balances.iter().fold(0, |paws: u64, (_, count)| paws.saturating_add(*count))),
+        details: format!("{} of {} with {}", mode.as_str(), file.display(), summary),
+        created_at: Utc::now()
+    }).await?;
+
+    // A running bot keeps the old settings until it restarts
+    println!("Imported {} into {}, to {} {}.", file.display(), guild_id, verb, summary);
+    Ok(())
+}
+
+async fn set_balance(store: &dyn PawStore, guild_id: u64, user_id: u64, count: u64, note: Option<String>) -> Result<(), Error> {
+    let previous = store.set_paw_count(user_id, guild_id, OPERATOR_ID, count).await?;
+
+    let mut details = format!("set from {} to {} on the command line", previous, count);
+    if let Some(note) = note.filter(|note| !note.trim().is_empty()) {
+        details.push_str(&format!(": {}", note.trim()));
+    }
+    store.add_audit_entry(&AuditEntry {
+        id: 0,
+        server_id: guild_id,
+        actor_id: OPERATOR_ID,
+        action: AdminAction::Set,
+        target_id: Some(user_id),
+        amount: Some(count),
+        details,
+        created_at: Utc::now()
+    }).await?;
+
+    println!("Set {} in {} from {} to {} paws", user_id, guild_id, previous, count);
+    Ok(())
+}
+
+async fn show_settings(store: &dyn PawStore, defaults: &ServerSettings, guild_id: u64) -> Result<(), Error> {
+    let settings = store.get_server_settings(guild_id).await?;
+    match settings {
+        Some(_) => println!("Settings of {}:", guild_id),
+        None => println!("{} uses the default settings:", guild_id)
+    }
+
+    let settings = settings.unwrap_or_else(|| defaults.clone());
+    for key in (0..).map_while(SettingKey::from_index) {
+        let value = setting_value(&settings, key);
+        let default = if value == setting_value(defaults, key) {" (default)"} else {""};
+        println!("{} = {}{}", key.name(), value, default);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,401 @@
+use super::{actor_mention, get_guild_id, Context, Error};
+use super::games::house_edge;
+use super::history::ledger;
+use crate::helpers::backup::{describe_import, BackupFormat, EconomyBackup};
+use crate::helpers::structs::{AdminAction, AuditEntry, ImportMode, LedgerReason, Transfer, TransferResult};
+use ::serenity::all::Mentionable;
+use chrono::Utc;
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use serenity::builder::{CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::collector::ComponentInteractionCollector;
+use serenity::ButtonStyle;
+use std::time::Duration;
+
+// How long the reset confirmation waits before giving up
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+// How many entries /paw admin audit shows
+const AUDIT_LIMIT: u64 = 15;
+
+// Largest file /paw admin import downloads
+const MAX_IMPORT_BYTES: u32 = 8 * 1024 * 1024;
+
+// How many problems with an import are listed before the rest are counted
+const MAX_PROBLEMS: usize = 10;
+
+// Most paws one admin command can grant, revoke, set or move, the same as the
+// #[max] of their amounts. That is the largest bound Discord accepts for an option,
+// 2^53 - 1, and only slash commands check it themselves.
+const MAX_AMOUNT: u64 = 9_007_199_254_740_991;
+
+// Members are mentioned by name without being pinged
+async fn reply(ctx: Context<'_>, content: String) -> Result<(), Error> {
+    ctx.send(CreateReply::default()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new())
+        .ephemeral(true)
+    ).await?;
+    Ok(())
+}
+
+// Every admin action is written down, with the moderator's note if they left one
+async fn audit(ctx: Context<'_>, action: AdminAction, target_id: Option<u64>, amount: Option<u64>, details: String, note: Option<String>) -> Result<(), Error> {
+    let details = match note {
+        Some(note) if !note.trim().is_empty() => format!("{}: {}", details, note.trim()),
+        _ => details
+    };
+
+    ctx.data().store.add_audit_entry(&AuditEntry {
+        id: 0,
+        server_id: get_guild_id(ctx)?,
+        actor_id: ctx.author().id.get(),
+        action,
+        target_id,
+        amount,
+        details,
+        created_at: Utc::now()
+    }).await
+}
+
+fn paws(amount: u64) -> String {
+    format!("{} {}", amount, if amount != 1 {"paws"} else {"paw"})
+}
+
+fn too_many(amount: u64) -> Option<String> {
+    (amount > MAX_AMOUNT).then(|| format!("That's too many paws, the most is {}", paws(MAX_AMOUNT)))
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("grant","revoke","set","transfer","reset","export","import","audit_log","ledger","house_edge"),
+    description_localized("en-US","Moderator tools for balances, the ledger and the house")
+)]
+pub async fn admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Gives a member paws from the house"), prefix_command)]
+pub async fn grant(
+    ctx: Context<'_>,
+    #[description = "who gets the paws"]
+    who: serenity::User,
+    #[description = "how many paws"]
+    #[min = 1]
+    #[max = 9_007_199_254_740_991u64]
+    amount: u64,
+    #[description = "(optional) why, for the audit log"]
+    note: Option<String>
+) -> Result<(), Error> {
+    if let Some(refusal) = too_many(amount) {
+        return reply(ctx, refusal).await;
+    }
+    let guild_id = get_guild_id(ctx)?;
+    let transfer = Transfer {
+        server_id: guild_id,
+        actor_id: ctx.author().id.get(),
+        source_id: None,
+        target_id: Some(who.id.get()),
+        amount,
+        reason: LedgerReason::AdminGrant
+    };
+
+    let TransferResult::Completed { target_count: Some(count), .. } = ctx.data().store.transfer_paws(&transfer).await? else {
+        return Err("The house couldn't pay out a grant".into());
+    };
+    audit(ctx, AdminAction::Grant, Some(who.id.get()), Some(amount), format!("granted {}", paws(amount)), note).await?;
+
+    reply(ctx, format!("Granted {} to {}, who now has {}", paws(amount), who.mention(), paws(count))).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Takes paws from a member back to the house"), prefix_command)]
+pub async fn revoke(
+    ctx: Context<'_>,
+    #[description = "who loses the paws"]
+    who: serenity::User,
+    #[description = "how many paws"]
+    #[min = 1]
+    #[max = 9_007_199_254_740_991u64]
+    amount: u64,
+    #[description = "(optional) why, for the audit log"]
+    note: Option<String>
+) -> Result<(), Error> {
+    if let Some(refusal) = too_many(amount) {
+        return reply(ctx, refusal).await;
+    }
+    let guild_id = get_guild_id(ctx)?;
+    let transfer = Transfer {
+        server_id: guild_id,
+        actor_id: ctx.author().id.get(),
+        source_id: Some(who.id.get()),
+        target_id: None,
+        amount,
+        reason: LedgerReason::AdminRevoke
+    };
+
+    let count = match ctx.data().store.transfer_paws(&transfer).await? {
+        TransferResult::Completed { source_count, .. } => source_count.unwrap_or(0),
+        TransferResult::InsufficientFunds { available } => {
+            return reply(ctx, format!("{} only has {}, use /paw admin set to take them all", who.mention(), paws(available))).await;
+        }
+    };
+    audit(ctx, AdminAction::Revoke, Some(who.id.get()), Some(amount), format!("revoked {}", paws(amount)), note).await?;
+
+    reply(ctx, format!("Revoked {} from {}, who now has {}", paws(amount), who.mention(), paws(count))).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Sets a member's balance to an exact amount"), prefix_command)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "whose balance to set"]
+    who: serenity::User,
+    #[description = "their new balance"]
+    #[max = 9_007_199_254_740_991u64]
+    amount: u64,
+    #[description = "(optional) why, for the audit log"]
+    note: Option<String>
+) -> Result<(), Error> {
+    if let Some(refusal) = too_many(amount) {
+        return reply(ctx, refusal).await;
+    }
+    let guild_id = get_guild_id(ctx)?;
+    let previous = ctx.data().store.set_paw_count(who.id.get(), guild_id, ctx.author().id.get(), amount).await?;
+    audit(ctx, AdminAction::Set, Some(who.id.get()), Some(amount), format!("set from {} to {}", previous, amount), note).await?;
+
+    reply(ctx, format!("Set {} from {} to {}", who.mention(), paws(previous), paws(amount))).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Moves paws from one member to another"), prefix_command)]
+pub async fn transfer(
+    ctx: Context<'_>,
+    #[description = "who the paws come from"]
+    from: serenity::User,
+    #[description = "who the paws go to"]
+    to: serenity::User,
+    #[description = "how many paws"]
+    #[min = 1]
+    #[max = 9_007_199_254_740_991u64]
+    amount: u64,
+    #[description = "(optional) why, for the audit log"]
+    note: Option<String>
+) -> Result<(), Error> {
+    if from.id == to.id {
+        return reply(ctx, "Pick two different members to move paws between".to_string()).await;
+    }
+    if let Some(refusal) = too_many(amount) {
+        return reply(ctx, refusal).await;
+    }
+
+    let guild_id = get_guild_id(ctx)?;
+    let transfer = Transfer {
+        server_id: guild_id,
+        actor_id: ctx.author().id.get(),
+        source_id: Some(from.id.get()),
+        target_id: Some(to.id.get()),
+        amount,
+        reason: LedgerReason::AdminTransfer
+    };
+
+    let (from_count, to_count) = match ctx.data().store.transfer_paws(&transfer).await? {
+        TransferResult::Completed { source_count, target_count } => (source_count.unwrap_or(0), target_count.unwrap_or(0)),
+        TransferResult::InsufficientFunds { available } => {
+            return reply(ctx, format!("{} only has {}", from.mention(), paws(available))).await;
+        }
+    };
+    audit(ctx, AdminAction::Transfer, Some(to.id.get()), Some(amount), format!("moved {} from <@{}>", paws(amount), from.id), note).await?;
+
+    reply(ctx, format!(
+        "Moved {} from {} to {}. {} now has {}, {} has {}.",
+        paws(amount), from.mention(), to.mention(), from.mention(), paws(from_count), to.mention(), paws(to_count)
+    )).await
+}
+
+fn confirm_buttons(ctx_id: u64, expired: bool) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}confirm", ctx_id))
+            .label("Reset everything")
+            .style(ButtonStyle::Danger)
+            .disabled(expired),
+        CreateButton::new(format!("{}cancel", ctx_id))
+            .label("Cancel")
+            .style(ButtonStyle::Secondary)
+            .disabled(expired)
+    ])
+}
+
+#[poise::command(slash_command, description_localized("en-US","Takes every paw on the server back to the house"), prefix_command)]
+pub async fn reset(
+    ctx: Context<'_>,
+    #[description = "(optional) why, for the audit log"]
+    note: Option<String>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+
+    // Button ids start with the invocation id so presses on other resets are ignored
+    let ctx_id = ctx.id();
+    let confirm_id = format!("{}confirm", ctx_id);
+
+    let reply = ctx.send(CreateReply::default()
+        .content("⚠️ This takes every paw on the server back to the house. Balances are snapshotted first. Are you sure?")
+        .components(vec![confirm_buttons(ctx_id, false)])
+        .ephemeral(true)
+    ).await?;
+
+    let press = ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .author_id(ctx.author().id)
+        .timeout(CONFIRM_TIMEOUT)
+        .await;
+
+    // Cancelled or left alone, either way nothing happens
+    let press = match press {
+        Some(press) if press.data.custom_id == confirm_id => press,
+        press => {
+            if let Some(press) = press {
+                press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+            }
+            reply.edit(ctx, CreateReply::default()
+                .content("Nothing was reset.")
+                .components(vec![confirm_buttons(ctx_id, true)])
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    // Answer the press straight away, a big server can take longer to reset than
+    // Discord waits for it
+    press.create_response(ctx, CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .content("🧹 Resetting...")
+        .components(vec![])
+    )).await?;
+
+    let reset = ctx.data().store.reset_economy(guild_id, ctx.author().id.get(), Utc::now()).await?;
+    audit(
+        ctx, AdminAction::Reset, None, Some(reset.paws),
+        format!("took back {} from {} farmers, snapshot #{}", paws(reset.paws), reset.farmers, reset.snapshot_id), note
+    ).await?;
+
+    reply.edit(ctx, CreateReply::default()
+        .content(format!(
+            "🧹 Took back {} from {} {}. The balances from before are kept in snapshot #{}.",
+            paws(reset.paws), reset.farmers, if reset.farmers != 1 {"farmers"} else {"farmer"}, reset.snapshot_id
+        ))
+        .components(vec![])
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Saves the server's balances, limits and settings to a file"), prefix_command)]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "(optional) json or csv, json by default"]
+    format: Option<BackupFormat>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let format = format.unwrap_or(BackupFormat::Json);
+
+    let backup = EconomyBackup::from_data(&ctx.data().store.export_economy(guild_id).await?);
+    let file = CreateAttachment::bytes(backup.write(format)?, format!("paws-{}.{}", guild_id, format.as_str()));
+
+    ctx.send(CreateReply::default()
+        .content(format!(
+            "📦 {} {} and {} cooldown and streak {}",
+            backup.paw_count.len(), if backup.paw_count.len() != 1 {"balances"} else {"balance"},
+            backup.user_limits.len(), if backup.user_limits.len() != 1 {"records"} else {"record"}
+        ))
+        .attachment(file)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Loads balances, limits and settings from an export"), prefix_command)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "a file from /paw admin export"]
+    file: serenity::Attachment,
+    #[description = "merge keeps members missing from the file, replace drops them"]
+    mode: ImportMode,
+    #[description = "(optional) only check the file and say what it would change"]
+    dry_run: Option<bool>,
+    #[description = "(optional) json or csv, going by the file name if not given"]
+    format: Option<BackupFormat>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+
+    let Some(format) = format.or_else(|| BackupFormat::from_file_name(&file.filename)) else {
+        return reply(ctx, format!("Can't tell what kind of file {} is, pick a format", file.filename)).await;
+    };
+    if file.size > MAX_IMPORT_BYTES {
+        return reply(ctx, format!("{} is too big to import, the limit is {} MB", file.filename, MAX_IMPORT_BYTES / 1024 / 1024)).await;
+    }
+
+    // Downloading and checking can take longer than Discord waits for an answer
+    ctx.defer_ephemeral().await?;
+
+    let backup = match EconomyBackup::read(&file.download().await?, format) {
+        Ok(backup) => backup,
+        Err(error) => return reply(ctx, format!("Couldn't read {}: {}", file.filename, error)).await
+    };
+
+    let current = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let data = match backup.to_data(mode, &current, ctx.data().settings.defaults()) {
+        Ok(data) => data,
+        Err(problems) => {
+            let mut content = format!("{} can't be imported:\n{}", file.filename, problems.iter().take(MAX_PROBLEMS).cloned().collect::<Vec<String>>().join("\n"));
+            if problems.len() > MAX_PROBLEMS {
+                content.push_str(&format!("\n...and {} more", problems.len() - MAX_PROBLEMS));
+            }
+            return reply(ctx, content).await;
+        }
+    };
+
+    let summary = describe_import(&data, mode);
+    if dry_run.unwrap_or(false) {
+        let verb = if mode == ImportMode::Replace {"replace everything with"} else {"merge in"};
+        return reply(ctx, format!("🔍 Nothing was changed. Importing {} would {} {}.", file.filename, verb, summary)).await;
+    }
+
+    ctx.data().store.import_economy(guild_id, ctx.author().id.get(), &data, mode).await?;
+    ctx.data().settings.invalidate(guild_id);
+
+    let paws = data.balances.iter().fold(0, |paws: u64, (_, count)| paws.saturating_add(*count));
+    audit(ctx, AdminAction::Import, None, Some(paws), format!("{} of {} with {}", mode.as_str(), file.filename, summary), None).await?;
+
+    let verb = if mode == ImportMode::Replace {"Replaced everything with"} else {"Merged in"};
+    reply(ctx, format!("📥 {} {} from {}.", verb, summary, file.filename)).await
+}
+
+#[poise::command(slash_command, rename = "audit", description_localized("en-US","Shows the latest admin actions on this server"), prefix_command)]
+pub async fn audit_log(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let entries = ctx.data().store.get_audit_log(guild_id, AUDIT_LIMIT).await?;
+
+    let description = match entries.is_empty() {
+        true => "No admin actions yet".to_string(),
+        false => entries.iter()
+            .map(|entry| {
+                let target = entry.target_id.map(|id| format!(" <@{}>", id)).unwrap_or_default();
+                format!("<t:{}:f> {} `{}`{} · {}", entry.created_at.timestamp(), actor_mention(entry.actor_id), entry.action.as_str(), target, entry.details)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    ctx.send(CreateReply::default()
+        .embed(serenity::CreateEmbed::new()
+            .title("🛡️ Admin audit log")
+            .description(description)
+        )
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use super::{format_wait, get_guild_id, Context, Error};
+use super::reminders::ready_at;
+use crate::helpers::{daily, durations::Locale, structs::{ReminderAction, ReminderDelivery}};
+use chrono::Utc;
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use serenity::builder::CreateEmbed;
+
+#[poise::command(slash_command, description_localized("en-US","Shows when you can next claim, gamble and steal"), prefix_command)]
+pub async fn cooldowns(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+    let settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let reminders = ctx.data().store.get_reminders(guild_id, user_id).await?;
+    let now = Utc::now();
+
+    let mut description = "".to_string();
+    for (action, emoji) in [(ReminderAction::Daily, "📅"), (ReminderAction::Gamble, "🎲"), (ReminderAction::Steal, "🧤")] {
+        let availability = match ready_at(ctx, &settings, guild_id, action, now).await? {
+            Some(available_at) => format!("ready {}", format_wait(&settings, available_at, now, Locale::English)),
+            None => "ready now".to_string()
+        };
+        description.push_str(&format!("{} {}: {}", emoji, action.as_str(), availability));
+
+        // Worth knowing before it's too late
+        if action == ReminderAction::Daily {
+            let streak = ctx.data().store.get_daily_streak(user_id, guild_id).await?;
+            let current = daily::active_streak(&settings, &streak, now);
+            if let Some(last_claimed) = streak.last_claimed.filter(|_| current > 0) {
+                let breaks_at = daily::streak_breaks_at(&settings, last_claimed);
+                let day_word = if current != 1 {"days"} else {"day"};
+                description.push_str(&format!(", your {} {} streak runs out {}", current, day_word, format_wait(&settings, breaks_at, now, Locale::English)));
+            }
+        }
+
+        match reminders.iter().find(|reminder| reminder.action == action).map(|reminder| reminder.delivery) {
+            Some(ReminderDelivery::Dm) => description.push_str(" 🔔 by DM"),
+            Some(ReminderDelivery::Channel) => description.push_str(" 🔔 in channel"),
+            None => {}
+        }
+        description.push('\n');
+    }
+
+    let embed = CreateEmbed::new()
+        .title("⏱️ Cooldowns ⏱️")
+        .description(description)
+        .footer(serenity::CreateEmbedFooter::new("Get a ping when they're ready with /paw remind"));
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
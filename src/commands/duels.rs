@@ -0,0 +1,157 @@
+use super::{get_guild_id, Context, Error};
+use crate::helpers::{duels::DUEL_TIMEOUT, structs::{Duel, DuelResult}};
+use ::serenity::all::Mentionable;
+use chrono::Utc;
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use rand::Rng;
+use serenity::builder::{CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::collector::ComponentInteractionCollector;
+use serenity::ButtonStyle;
+
+fn duel_buttons(ctx_id: u64, expired: bool) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}accept", ctx_id))
+            .label("Accept")
+            .style(ButtonStyle::Success)
+            .disabled(expired),
+        CreateButton::new(format!("{}decline", ctx_id))
+            .label("Decline")
+            .style(ButtonStyle::Danger)
+            .disabled(expired)
+    ])
+}
+
+#[poise::command(slash_command, description_localized("en-US","Challenge someone to a duel, winner takes both stakes"), prefix_command)]
+pub async fn duel(
+    ctx: Context<'_>,
+    #[description = "who to challenge"]
+    who: serenity::User,
+    #[description = "how many paws each of you puts up"]
+    #[min = 1]
+    amount: u64
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+
+    if who.id == ctx.author().id || who.bot {
+        ctx.send(CreateReply::default()
+            .content("You can only duel other members")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let mut duel = Duel {
+        id: 0,
+        server_id: guild_id,
+        challenger_id: user_id,
+        target_id: who.id.get(),
+        stake: amount,
+        expires_at: Utc::now() + chrono::Duration::from_std(DUEL_TIMEOUT)?
+    };
+
+    // The challenger's stake is held from the start, so they can't spend it while waiting
+    let Some(id) = ctx.data().store.open_duel(&duel).await? else {
+        ctx.send(CreateReply::default()
+            .content("You can only duel for as many paws as you have!")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    };
+    duel.id = id;
+
+    let paw_word = if amount != 1 {"paws"} else {"paw"};
+    let challenge = format!(
+        "⚔️ {}, {} challenges you to a duel for {} {}! Winner takes {}.",
+        who.mention(), ctx.author().mention(), amount, paw_word, amount * 2
+    );
+
+    // Button ids start with the invocation id so presses on other duels are ignored
+    let ctx_id = ctx.id();
+    let accept_id = format!("{}accept", ctx_id);
+    let decline_id = format!("{}decline", ctx_id);
+
+    let reply = ctx.send(CreateReply::default()
+        .content(format!("{} The challenge runs out <t:{}:R>.", challenge, duel.expires_at.timestamp()))
+        .components(vec![duel_buttons(ctx_id, false)])
+        .allowed_mentions(CreateAllowedMentions::new().users([who.id]))
+    ).await?;
+
+    let mut result = None;
+    while let Ok(remaining) = (duel.expires_at - Utc::now()).to_std() {
+        let Some(press) = ComponentInteractionCollector::new(ctx)
+            .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+            .timeout(remaining)
+            .await
+        else {
+            break;
+        };
+
+        // The challenger can take it back, but only the target can accept
+        let declined = press.data.custom_id == decline_id && (press.user.id == who.id || press.user.id == ctx.author().id);
+        let accepted = press.data.custom_id == accept_id && press.user.id == who.id;
+
+        if declined {
+            press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+            result = Some(format!("{} The duel was called off.", challenge));
+            break;
+        }
+
+        if !accepted {
+            press.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+                .content(format!("Only {} can answer this duel, use /paw duel to start your own", who.name))
+                .ephemeral(true)
+            )).await?;
+            continue;
+        }
+
+        // Even odds, whoever threw down the challenge
+        let winner = if rand::thread_rng().gen_bool(0.5) {ctx.author().clone()} else {who.clone()};
+        let loser = if winner.id == who.id {ctx.author().clone()} else {who.clone()};
+
+        match ctx.data().store.accept_duel(&duel, winner.id.get()).await? {
+            DuelResult::Fought { winner_count, loser_count } => {
+                press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+
+                let winner_word = if winner_count != 1 {"paws"} else {"paw"};
+                let loser_word = if loser_count != 1 {"paws"} else {"paw"};
+                reply.edit(ctx, CreateReply::default()
+                    .content(format!(
+                        "⚔️ {} won the duel against {} and takes {} paws! {} now has {} {}, {} has {} {}.",
+                        winner.mention(), loser.mention(), amount * 2, winner.name, winner_count, winner_word, loser.name, loser_count, loser_word
+                    ))
+                    .components(vec![])
+                    .allowed_mentions(CreateAllowedMentions::new())
+                ).await?;
+                return Ok(());
+            }
+            DuelResult::InsufficientFunds { available } => {
+                let available_word = if available != 1 {"paws"} else {"paw"};
+                press.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+                    .content(format!("You need {} {} to accept, you only have {} {}", amount, paw_word, available, available_word))
+                    .ephemeral(true)
+                )).await?;
+            }
+            DuelResult::Closed => {
+                press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+                break;
+            }
+        }
+    }
+
+    // Declined or run out, either way the challenger gets their stake back
+    let refunded = ctx.data().store.cancel_duel(&duel).await?.is_some();
+    let mut content = result.unwrap_or_else(|| format!("{} Nobody answered in time.", challenge));
+    if refunded {
+        content.push_str(&format!(" {} got their {} {} back.", ctx.author().name, amount, paw_word));
+    }
+
+    reply.edit(ctx, CreateReply::default()
+        .content(content)
+        .components(vec![duel_buttons(ctx_id, true)])
+        .allowed_mentions(CreateAllowedMentions::new())
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,440 @@
+use super::{format_wait, get_guild_id, locale, outcome_embed, Context, Error};
+use super::reminders::queue_reminder;
+use crate::helpers::durations::Locale;
+use crate::helpers::games::{self, Blackjack, BlackjackOutcome, CoinSide, DiceBet};
+use crate::helpers::outcome::{Outcome, OutcomeResolver, WeightedOutcomes};
+use crate::helpers::stakes;
+use crate::helpers::structs::{Cooldown, Game, GameResult, GameStake, ItemEffect, OpenGame, ReminderAction, ServerSettings, TransferResult};
+use chrono::{DateTime, Utc};
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use rand::thread_rng;
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::collector::ComponentInteractionCollector;
+use std::time::Duration;
+use tracing::log::warn;
+
+// How long a blackjack player has for each move before they stand
+const TURN_TIMEOUT: Duration = Duration::from_secs(60);
+
+// A game that is allowed to go ahead, its cooldown has already started and the
+// house is holding the stake
+struct Table {
+    guild_id: u64,
+    settings: ServerSettings,
+    now: DateTime<Utc>,
+    open: OpenGame
+}
+
+// What a game ended up paying, and the balance it left the player with
+struct Settled {
+    payout: i64,
+    balance: u64
+}
+
+async fn refuse(ctx: Context<'_>, content: impl Into<String>) -> Result<(), Error> {
+    ctx.send(CreateReply::default()
+        .content(content)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+// Every game shares the gamble cooldown and the server's stake limits. Stakes too
+// small to win anything at the game's odds are turned away too. Players are told
+// why they can't play and None is returned, otherwise the stake is held until the
+// game is settled.
+async fn sit_down(ctx: Context<'_>, stake: u64, winnings: impl FnOnce(&ServerSettings) -> u64) -> Result<Option<Table>, Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+    let last_gambled = ctx.data().store.get_last_used(user_id, guild_id, Cooldown::Gamble).await?;
+    let settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let now = Utc::now();
+
+    // Limit how often a user can gamble
+    let available_at = Cooldown::Gamble.available_at(&settings, last_gambled);
+    if let Some(available_at) = available_at.filter(|available_at| now < *available_at) {
+        let refusal = match locale(ctx) {
+            Locale::English => format!("🚫 🐶 gambling addiction is a serious problem. Regulations require a wait. Try again {}.", format_wait(&settings, available_at, now, Locale::English)),
+            Locale::German => format!("🚫 🐶 Spielsucht ist ein ernstes Problem. Die Vorschriften verlangen eine Pause. Versuch es {} noch einmal.", format_wait(&settings, available_at, now, Locale::German)),
+            Locale::French => format!("🚫 🐶 l'addiction au jeu est un problème sérieux. La réglementation impose une pause. Réessaie {}.", format_wait(&settings, available_at, now, Locale::French)),
+            Locale::Spanish => format!("🚫 🐶 la adicción al juego es un problema serio. Las normas exigen una espera. Vuelve a intentarlo {}.", format_wait(&settings, available_at, now, Locale::Spanish))
+        };
+        refuse(ctx, refusal).await?;
+        return Ok(None);
+    }
+
+    // Users can only gamble as many paws as they have, within the server's limits
+    let paw_count = ctx.data().store.get_paw_count(user_id, guild_id).await?;
+    if let Err(reason) = stakes::check_stake(&settings, stake, paw_count) {
+        refuse(ctx, reason).await?;
+        return Ok(None);
+    }
+
+    if winnings(&settings) == 0 {
+        let paw_word = if stake != 1 {"paws"} else {"paw"};
+        refuse(ctx, format!("A win at these odds wouldn't pay anything on {} {}, try a bigger stake", stake, paw_word)).await?;
+        return Ok(None);
+    }
+
+    // Starting the cooldown and holding the stake happen together, so only one of
+    // several games sent at once gets played
+    let placed = GameStake { server_id: guild_id, user_id, stake, previous: last_gambled, placed_at: now };
+    match ctx.data().store.place_stake(&placed).await? {
+        Some(TransferResult::Completed { .. }) => {}
+        Some(TransferResult::InsufficientFunds { .. }) => {
+            refuse(ctx, "You can only gamble as many paws as you have!").await?;
+            return Ok(None);
+        }
+        None => {
+            refuse(ctx, "🚫 🐶 gambling addiction is a serious problem. You're already playing, finish that game first.").await?;
+            return Ok(None);
+        }
+    }
+    queue_reminder(ctx, guild_id, ReminderAction::Gamble, now + settings.gamble_interval.duration(), now).await;
+
+    Ok(Some(Table { guild_id, settings, now, open: placed.open_game() }))
+}
+
+// Gives the held stake back when a game fails before it's settled, like when
+// Discord can't be reached mid-hand. Settled games have nothing left to refund.
+async fn call_off(ctx: Context<'_>, table: &Table, played: Result<(), Error>) -> Result<(), Error> {
+    if played.is_err() {
+        if let Err(error) = ctx.data().store.refund_game(&table.open).await {
+            warn!("Couldn't refund {} paws to {} for a failed game: {:?}", table.open.stake, table.open.user_id, error);
+        }
+    }
+
+    played
+}
+
+// Pays back the held stake with any winnings and records the game
+async fn settle(ctx: Context<'_>, table: &Table, game: Game, stake: u64, payout: i64) -> Result<Settled, Error> {
+    let user_id = ctx.author().id.get();
+    let result = GameResult {
+        server_id: table.guild_id,
+        user_id,
+        game,
+        stake,
+        payout,
+        played_at: table.now
+    };
+
+    let Some(balance) = ctx.data().store.settle_game(&result).await? else {
+        return Err("This game took too long and was called off, your stake has been given back".into());
+    };
+
+    Ok(Settled { payout, balance })
+}
+
+// How a game went for the player's paws
+fn settled_line(settled: &Settled) -> String {
+    let amount = settled.payout.unsigned_abs();
+    let amount_paw_word = if amount != 1 {"paws"} else {"paw"};
+    let new_paw_word = if settled.balance != 1 {"paws"} else {"paw"};
+
+    match settled.payout {
+        0 => format!("You got your stake back and still have {} {}.", settled.balance, new_paw_word),
+        payout if payout > 0 => format!("You won {} {}, giving you a total of {} {}.", amount, amount_paw_word, settled.balance, new_paw_word),
+        _ => format!("You lost {} {}, giving you a total of {} {}.", amount, amount_paw_word, settled.balance, new_paw_word)
+    }
+}
+
+#[poise::command(slash_command, description_localized("en-US","Test your odds"), prefix_command)]
+pub async fn gamble(
+    ctx: Context<'_>,
+    #[description = "how many paws to bet"]
+    #[min = 1]
+    stake: u64
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let Some(table) = sit_down(ctx, stake, |settings| stakes::winnings(settings, stake)).await? else {
+        return Ok(());
+    };
+
+    let played = async {
+        // A charm, if they hold one, is used up to improve the odds
+        let mut outcomes = WeightedOutcomes::for_gamble(&table.settings)?;
+        let charm = ctx.data().store.consume_item(user_id, table.guild_id, ItemEffect::GambleCharm).await?;
+        if let Some(charm) = &charm {
+            outcomes = outcomes.boost(Outcome::Success, charm.strength as u32);
+        }
+
+        // Let the server's odds decide how it went
+        let outcome = outcomes.resolve(&mut thread_rng());
+
+        // Winnings come from the house and losses go to it
+        let settlement = outcome.settle(stake, stakes::winnings(&table.settings, stake));
+        let settled = settle(ctx, &table, Game::Gamble, stake, settlement).await?;
+
+        let amount = settled.payout.unsigned_abs();
+        let new_paws = settled.balance;
+        let amount_paw_word = if amount != 1 {"paws"} else {"paw"};
+        let new_paw_word = if new_paws != 1 {"paws"} else {"paw"};
+
+        let mut description = match outcome {
+            Outcome::CriticalSuccess => format!("Jackpot! Your gambling paid off big, you won {} {}, giving you a total of {} {}.", amount, amount_paw_word, new_paws, new_paw_word),
+            Outcome::Success => format!("Your gambling paid off, you won {} {}, giving you a total of {} {}.", amount, amount_paw_word, new_paws, new_paw_word),
+            Outcome::PartialSuccess => format!("Your gambling sort of paid off, you won {} {}, giving you a total of {} {}.", amount, amount_paw_word, new_paws, new_paw_word),
+            Outcome::Failure | Outcome::Caught => format!("Your gambling sucked, you lost {} {}, giving you a total of {} {}.", amount, amount_paw_word, new_paws, new_paw_word)
+        };
+
+        if let Some(charm) = charm {
+            description = format!("🍀 Your {} added {}% to your odds.\n{}", charm.name, charm.strength, description);
+        }
+
+        ctx.send(CreateReply::default()
+            .embed(outcome_embed("🎲 🐶 🎲", description, new_paws, outcome.is_success()))
+        ).await?;
+
+        Ok(())
+    }.await;
+
+    call_off(ctx, &table, played).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Call heads or tails"), prefix_command)]
+pub async fn coinflip(
+    ctx: Context<'_>,
+    #[description = "the side you think it lands on"]
+    side: CoinSide,
+    #[description = "how many paws to bet"]
+    #[min = 1]
+    stake: u64
+) -> Result<(), Error> {
+    let Some(table) = sit_down(ctx, stake, |settings| stakes::winnings_at(settings, stake, 50)).await? else {
+        return Ok(());
+    };
+
+    let played = async {
+        let landed = games::flip_coin(&mut thread_rng());
+        let payout = if landed == side {stakes::winnings_at(&table.settings, stake, 50) as i64} else {-(stake as i64)};
+        let settled = settle(ctx, &table, Game::Coinflip, stake, payout).await?;
+
+        let description = format!("It landed on {}! {}\n", landed.as_str(), settled_line(&settled));
+
+        ctx.send(CreateReply::default()
+            .embed(outcome_embed("🪙 🐶 🪙", description, settled.balance, settled.payout > 0))
+        ).await?;
+
+        Ok(())
+    }.await;
+
+    call_off(ctx, &table, played).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Bet on a roll of 1 to 100 landing over or under a number"), prefix_command)]
+pub async fn dice(
+    ctx: Context<'_>,
+    #[description = "whether the roll lands over or under the target"]
+    bet: DiceBet,
+    #[description = "the number to beat, a roll of it loses"]
+    #[min = 5]
+    #[max = 95]
+    target: u32,
+    #[description = "how many paws to bet"]
+    #[min = 1]
+    stake: u64
+) -> Result<(), Error> {
+    let chance = bet.chance(target);
+    let Some(table) = sit_down(ctx, stake, |settings| stakes::winnings_at(settings, stake, chance)).await? else {
+        return Ok(());
+    };
+
+    let played = async {
+        let roll = games::roll_dice(&mut thread_rng());
+        let won = bet.wins(target, roll);
+        let payout = if won {stakes::winnings_at(&table.settings, stake, chance) as i64} else {-(stake as i64)};
+        let settled = settle(ctx, &table, Game::Dice, stake, payout).await?;
+
+        let description = format!(
+            "You bet {} {} ({}% chance, pays {:.2}x) and rolled **{}**. {}\n",
+            bet.as_str(), target, chance, stakes::multiplier_at(&table.settings, chance), roll, settled_line(&settled)
+        );
+
+        ctx.send(CreateReply::default()
+            .embed(outcome_embed("🎲 🐶 🎲", description, settled.balance, won))
+        ).await?;
+
+        Ok(())
+    }.await;
+
+    call_off(ctx, &table, played).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Spin the reels"), prefix_command)]
+pub async fn slots(
+    ctx: Context<'_>,
+    #[description = "how many paws to bet"]
+    #[min = 1]
+    stake: u64
+) -> Result<(), Error> {
+    // The smallest win pays twice the stake back
+    let Some(table) = sit_down(ctx, stake, |_| stake).await? else {
+        return Ok(());
+    };
+
+    let played = async {
+        let reels = games::spin_reels(&mut thread_rng());
+        let multiplier = games::slots_multiplier(&reels);
+        let payout = if multiplier > 0 {(stake * (multiplier - 1)) as i64} else {-(stake as i64)};
+        let settled = settle(ctx, &table, Game::Slots, stake, payout).await?;
+
+        let description = format!("**[ {} | {} | {} ]**\n{}\n", reels[0], reels[1], reels[2], settled_line(&settled));
+        let embed = outcome_embed("🎰 🐶 🎰", description, settled.balance, multiplier > 0)
+            .footer(serenity::CreateEmbedFooter::new(games::paytable()));
+
+        ctx.send(CreateReply::default()
+            .embed(embed)
+        ).await?;
+
+        Ok(())
+    }.await;
+
+    call_off(ctx, &table, played).await
+}
+
+fn blackjack_description(game: &Blackjack, finished: bool) -> String {
+    // The dealer's second card stays face down until the hand is over
+    let dealer = if finished {
+        format!("{} ({})", game.dealer.cards(), game.dealer.value())
+    } else {
+        format!("{} `??`", game.dealer.0[0])
+    };
+
+    format!("Your hand: {} ({})\nDealer: {}\n", game.player.cards(), game.player.value(), dealer)
+}
+
+fn blackjack_buttons(ctx_id: u64) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}hit", ctx_id))
+            .label("Hit"),
+        CreateButton::new(format!("{}stand", ctx_id))
+            .label("Stand")
+    ])
+}
+
+#[poise::command(slash_command, description_localized("en-US","Play a hand of blackjack against the dealer"), prefix_command)]
+pub async fn blackjack(
+    ctx: Context<'_>,
+    #[description = "how many paws to bet"]
+    #[min = 1]
+    stake: u64
+) -> Result<(), Error> {
+    let Some(table) = sit_down(ctx, stake, |_| stake).await? else {
+        return Ok(());
+    };
+
+    let played = async {
+        let mut game = Blackjack::deal(&mut thread_rng());
+
+        // Button ids start with the invocation id so presses on other hands are ignored
+        let ctx_id = ctx.id();
+        let hit_id = format!("{}hit", ctx_id);
+        let stand_id = format!("{}stand", ctx_id);
+
+        let natural = game.natural();
+        let reply = ctx.send(CreateReply::default()
+            .embed(CreateEmbed::new().title("🃏 🐶 🃏").description(blackjack_description(&game, natural.is_some())))
+            .components(if natural.is_some() {vec![]} else {vec![blackjack_buttons(ctx_id)]})
+        ).await?;
+
+        let outcome = match natural {
+            Some(outcome) => outcome,
+            None => loop {
+                let press = ComponentInteractionCollector::new(ctx)
+                    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+                    .timeout(TURN_TIMEOUT)
+                    .await;
+
+                // Players who walk away stand on what they have
+                let Some(press) = press else {
+                    break game.stand(&mut thread_rng());
+                };
+
+                if press.user.id != ctx.author().id {
+                    press.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+                        .content(format!("Only {} can play this hand, use /paw blackjack for your own", ctx.author().name))
+                        .ephemeral(true)
+                    )).await?;
+                    continue;
+                }
+
+                press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+
+                if press.data.custom_id == stand_id {
+                    break game.stand(&mut thread_rng());
+                } else if press.data.custom_id == hit_id {
+                    if let Some(outcome) = game.hit(&mut thread_rng()) {
+                        break outcome;
+                    }
+                    // Nothing is gained by hitting on 21
+                    if game.player.value() == 21 {
+                        break game.stand(&mut thread_rng());
+                    }
+                } else {
+                    continue;
+                }
+
+                reply.edit(ctx, CreateReply::default()
+                    .embed(CreateEmbed::new().title("🃏 🐶 🃏").description(blackjack_description(&game, false)))
+                    .components(vec![blackjack_buttons(ctx_id)])
+                ).await?;
+            }
+        };
+
+        let settled = settle(ctx, &table, Game::Blackjack, stake, outcome.settle(stake)).await?;
+
+        let verdict = match outcome {
+            BlackjackOutcome::Blackjack => "Blackjack!",
+            BlackjackOutcome::DealerBust => "The dealer went bust!",
+            BlackjackOutcome::Win => "You beat the dealer!",
+            BlackjackOutcome::Push => "It's a push.",
+            BlackjackOutcome::Loss => "The dealer wins.",
+            BlackjackOutcome::Bust => "You went bust.",
+            BlackjackOutcome::DealerBlackjack => "The dealer has blackjack."
+        };
+        let description = format!("{}{} {}\n", blackjack_description(&game, true), verdict, settled_line(&settled));
+
+        reply.edit(ctx, CreateReply::default()
+            .embed(outcome_embed("🃏 🐶 🃏", description, settled.balance, settled.payout > 0))
+            .components(vec![])
+        ).await?;
+
+        Ok(())
+    }.await;
+
+    call_off(ctx, &table, played).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Shows how much of the stakes each game has kept for the house"), prefix_command)]
+pub async fn house_edge(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let stats = ctx.data().store.get_game_stats(guild_id).await?;
+
+    let mut description = "".to_string();
+    for game in &stats {
+        let game_word = if game.played != 1 {"games"} else {"game"};
+        let paw_word = if game.staked != 1 {"paws"} else {"paw"};
+        description.push_str(&format!("🎲 {}: {} {}, {} {} staked, house edge {:.2}%\n", game.game.as_str(), game.played, game_word, game.staked, paw_word, game.house_edge()));
+    }
+
+    if stats.is_empty() {
+        description.push_str("Nothing has been played on this server yet");
+    }
+
+    let embed = CreateEmbed::new()
+        .title("🏦 House edge 🏦")
+        .description(description)
+        .footer(serenity::CreateEmbedFooter::new("A negative edge means players are ahead"));
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
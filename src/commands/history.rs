@@ -0,0 +1,174 @@
+use super::{actor_mention, get_guild_id, Context, Error};
+use crate::helpers::leaderboard::{page_count, page_offset, PAGE_SIZE};
+use crate::helpers::structs::{LedgerEntry, LedgerFilter, LedgerReason};
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::collector::ComponentInteractionCollector;
+use std::time::Duration;
+
+// How long the buttons keep working after the last press
+const BUTTON_TIMEOUT: Duration = Duration::from_secs(120);
+
+struct HistoryPage {
+    page: u64,
+    page_count: u64,
+    entries: Vec<LedgerEntry>
+}
+
+async fn get_page(ctx: Context<'_>, guild_id: u64, filter: LedgerFilter, page: u64) -> Result<HistoryPage, Error> {
+    let store = ctx.data().store.as_ref();
+
+    // Pages can disappear while someone is scrolling
+    let page_count = page_count(store.count_ledger(guild_id, filter).await?);
+    let page = page.clamp(1, page_count);
+    let entries = store.get_ledger(guild_id, filter, page_offset(page), PAGE_SIZE).await?;
+
+    Ok(HistoryPage { page, page_count, entries })
+}
+
+fn party(id: Option<u64>) -> String {
+    match id {
+        Some(id) => format!("<@{}>", id),
+        None => "the house".to_string()
+    }
+}
+
+// One transfer, from the side of the member whose history it is if there is one
+fn entry_line(entry: &LedgerEntry, member: Option<u64>) -> String {
+    let mut line = match member {
+        Some(member) if entry.target_id == Some(member) => {
+            format!("<t:{}:f> `+{}` {} · {}", entry.created_at.timestamp(), entry.amount, entry.reason.as_str(), party(entry.source_id))
+        }
+        Some(_) => {
+            format!("<t:{}:f> `-{}` {} · {}", entry.created_at.timestamp(), entry.amount, entry.reason.as_str(), party(entry.target_id))
+        }
+        None => {
+            format!("<t:{}:f> `{}` {} · {} → {}", entry.created_at.timestamp(), entry.amount, entry.reason.as_str(), party(entry.source_id), party(entry.target_id))
+        }
+    };
+
+    // Someone moved paws that weren't theirs
+    if entry.source_id != Some(entry.actor_id) && entry.target_id != Some(entry.actor_id) {
+        line.push_str(&format!(" (by {})", actor_mention(entry.actor_id)));
+    }
+
+    line
+}
+
+fn history_embed(title: &str, history: &HistoryPage, member: Option<u64>) -> CreateEmbed {
+    let description = match history.entries.is_empty() {
+        true => "No paws have moved yet 🌵".to_string(),
+        false => history.entries.iter()
+            .map(|entry| entry_line(entry, member))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .footer(serenity::CreateEmbedFooter::new(format!("Page {}/{}", history.page, history.page_count)))
+}
+
+fn buttons(ctx_id: u64, history: &HistoryPage, expired: bool) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}previous", ctx_id))
+            .emoji('◀')
+            .disabled(expired || history.page <= 1),
+        CreateButton::new(format!("{}next", ctx_id))
+            .emoji('▶')
+            .disabled(expired || history.page >= history.page_count)
+    ])
+}
+
+// Sends the first page and flips through the rest as the buttons are pressed
+async fn show_history(ctx: Context<'_>, title: String, filter: LedgerFilter, member: Option<u64>) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+
+    // Button ids start with the invocation id so presses on other histories are ignored
+    let ctx_id = ctx.id();
+    let previous_id = format!("{}previous", ctx_id);
+    let next_id = format!("{}next", ctx_id);
+
+    let mut history = get_page(ctx, guild_id, filter, 1).await?;
+    let reply = ctx.send(CreateReply::default()
+        .embed(history_embed(&title, &history, member))
+        .components(vec![buttons(ctx_id, &history, false)])
+        .ephemeral(true)
+    ).await?;
+
+    while let Some(press) = ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(BUTTON_TIMEOUT)
+        .await
+    {
+        if press.user.id != ctx.author().id {
+            press.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+                .content(format!("Only {} can flip through this history, use /paw history for your own", ctx.author().name))
+                .ephemeral(true)
+            )).await?;
+            continue;
+        }
+
+        let page = if press.data.custom_id == previous_id {
+            history.page.saturating_sub(1)
+        } else if press.data.custom_id == next_id {
+            history.page + 1
+        } else {
+            continue;
+        };
+
+        press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+        history = get_page(ctx, guild_id, filter, page).await?;
+
+        reply.edit(ctx, CreateReply::default()
+            .embed(history_embed(&title, &history, member))
+            .components(vec![buttons(ctx_id, &history, false)])
+        ).await?;
+    }
+
+    // Leave the last page up, but show the buttons no longer do anything
+    reply.edit(ctx, CreateReply::default()
+        .embed(history_embed(&title, &history, member))
+        .components(vec![buttons(ctx_id, &history, true)])
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Shows where your paws came from and went to"), prefix_command)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "(optional) member to show the history of"]
+    who: Option<serenity::User>
+) -> Result<(), Error> {
+    let who = who.as_ref().unwrap_or(ctx.author());
+    let filter = LedgerFilter { user_id: Some(who.id.get()), reason: None };
+
+    show_history(ctx, format!("📜 History of {} 📜", who.name), filter, Some(who.id.get())).await
+}
+
+#[poise::command(slash_command, description_localized("en-US","Shows every transfer of paws on this server"), prefix_command)]
+pub async fn ledger(
+    ctx: Context<'_>,
+    #[description = "(optional) only show transfers of this kind"]
+    reason: Option<LedgerReason>,
+    #[description = "(optional) only show transfers to or from this member"]
+    who: Option<serenity::User>
+) -> Result<(), Error> {
+    let filter = LedgerFilter {
+        user_id: who.as_ref().map(|who| who.id.get()),
+        reason
+    };
+
+    let mut title = match &who {
+        Some(who) => format!("📜 Ledger of {}", who.name),
+        None => "📜 Ledger".to_string()
+    };
+    if let Some(reason) = reason {
+        title.push_str(&format!(", {} only", reason.as_str()));
+    }
+
+    show_history(ctx, title, filter, filter.user_id).await
+}
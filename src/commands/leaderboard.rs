@@ -0,0 +1,198 @@
+use super::{get_guild_id, Context, Error};
+use crate::helpers::leaderboard::{self, LeaderboardPage, Standing};
+use crate::helpers::{profiles::display_name, structs::UserProfile};
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use serenity::builder::{CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::collector::ComponentInteractionCollector;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// How long the buttons keep working after the last press
+const BUTTON_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[poise::command(slash_command, description_localized("en-US","Take a gander at the paw leaderboard"), prefix_command)]
+pub async fn top(
+    ctx: Context<'_>,
+    #[description = "(optional) page number"]
+    #[min = 1]
+    page: Option<u32>,
+    #[description = "(optional) jump to the page you're on"]
+    me: Option<bool>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+
+    let my_page = if me.unwrap_or(false) {
+        get_standing(ctx, guild_id).await?.map(|standing| standing.page())
+    } else {
+        None
+    };
+    let page = my_page.unwrap_or(page.unwrap_or(1) as u64);
+
+    // Button ids start with the invocation id so presses on other leaderboards are ignored
+    let ctx_id = ctx.id();
+    let previous_id = format!("{}previous", ctx_id);
+    let next_id = format!("{}next", ctx_id);
+    let me_id = format!("{}me", ctx_id);
+
+    let (mut embed, mut leaderboard, mut standing) = render_page(ctx, guild_id, page).await?;
+    let reply = ctx.send(CreateReply::default()
+        .embed(embed.clone())
+        .components(vec![buttons(ctx_id, &leaderboard, standing, false)])
+    ).await?;
+
+    while let Some(press) = ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(BUTTON_TIMEOUT)
+        .await
+    {
+        if press.user.id != ctx.author().id {
+            press.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+                .content(format!("Only {} can flip through this leaderboard, use /paw top for your own", ctx.author().name))
+                .ephemeral(true)
+            )).await?;
+            continue;
+        }
+
+        let page = if press.data.custom_id == previous_id {
+            leaderboard.page.saturating_sub(1)
+        } else if press.data.custom_id == next_id {
+            leaderboard.page + 1
+        } else if press.data.custom_id == me_id {
+            // Balances may have moved since the leaderboard was sent
+            match get_standing(ctx, guild_id).await? {
+                Some(standing) => standing.page(),
+                None => leaderboard.page
+            }
+        } else {
+            continue;
+        };
+
+        // Looking up usernames can take longer than Discord waits for a response
+        press.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+
+        // Pages can disappear while someone is scrolling
+        let page = page.clamp(1, leaderboard.page_count.max(1));
+        (embed, leaderboard, standing) = render_page(ctx, guild_id, page).await?;
+
+        reply.edit(ctx, CreateReply::default()
+            .embed(embed.clone())
+            .components(vec![buttons(ctx_id, &leaderboard, standing, false)])
+        ).await?;
+    }
+
+    // Leave the last page up, but show the buttons no longer do anything
+    reply.edit(ctx, CreateReply::default()
+        .embed(embed)
+        .components(vec![buttons(ctx_id, &leaderboard, standing, true)])
+    ).await?;
+
+    Ok(())
+}
+
+async fn get_standing(ctx: Context<'_>, guild_id: u64) -> Result<Option<Standing>, Error> {
+    let store = ctx.data().store.as_ref();
+    let ranking_mode = ctx.data().settings.get(store, guild_id).await?.ranking_mode;
+
+    leaderboard::get_standing(store, guild_id, ctx.author().id.get(), ranking_mode).await
+}
+
+async fn render_page(ctx: Context<'_>, guild_id: u64, page: u64) -> Result<(CreateEmbed, LeaderboardPage, Option<Standing>), Error> {
+    let store = ctx.data().store.as_ref();
+    let ranking_mode = ctx.data().settings.get(store, guild_id).await?.ranking_mode;
+
+    let standing = get_standing(ctx, guild_id).await?;
+    let leaderboard = leaderboard::get_page(store, guild_id, page, ranking_mode).await?;
+
+    // Every name on the page in one go, instead of a request per farmer
+    let user_ids: Vec<u64> = leaderboard.entries.iter().map(|farmer| farmer.user_id).collect();
+    let profiles = ctx.data().profiles.get_many(ctx, store, &user_ids).await?;
+
+    let mut embed = CreateEmbed::new()
+        .title("🏆 Leaderboard\u{200B} 👑")
+        .description(leaderboard_description(ctx, &leaderboard, standing, &profiles))
+        .footer(serenity::CreateEmbedFooter::new(format!("Page {}/{}", leaderboard.page, leaderboard.page_count)));
+
+    // The top farmer gets their picture on the first page
+    let leader_avatar = leaderboard.entries.first()
+        .filter(|farmer| farmer.rank == 1)
+        .and_then(|farmer| profiles.get(&farmer.user_id))
+        .and_then(|profile| profile.avatar_url.clone());
+    if let Some(avatar_url) = leader_avatar {
+        embed = embed.thumbnail(avatar_url);
+    }
+
+    Ok((embed, leaderboard, standing))
+}
+
+fn buttons(ctx_id: u64, leaderboard: &LeaderboardPage, standing: Option<Standing>, expired: bool) -> CreateActionRow {
+    let on_my_page = standing.is_some_and(|standing| standing.page() == leaderboard.page);
+
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}previous", ctx_id))
+            .emoji('◀')
+            .disabled(expired || leaderboard.page <= 1),
+        CreateButton::new(format!("{}me", ctx_id))
+            .label("Jump to me")
+            .disabled(expired || standing.is_none() || on_my_page),
+        CreateButton::new(format!("{}next", ctx_id))
+            .emoji('▶')
+            .disabled(expired || leaderboard.page >= leaderboard.page_count)
+    ])
+}
+
+fn leaderboard_description(ctx: Context<'_>, leaderboard: &LeaderboardPage, standing: Option<Standing>, profiles: &HashMap<u64, UserProfile>) -> String {
+    // Top of embed content
+    let mut description = "".to_string();
+    description.push_str(&format!("🐶 {}\n",leaderboard.total_paws));
+    description.push_str(&format!("👨‍🌾 {}\n\n",leaderboard.farmers));
+    description.push_str("📈 Ranks 💪\n");
+
+    // Handle no content on page
+    if leaderboard.entries.is_empty() {
+        let page_word = if leaderboard.page_count != 1 {"pages"} else {"page"};
+        description.push_str(&format!("Page contains no farmers 🌵 there {} only {} {}", if leaderboard.page_count != 1 {"are"} else {"is"}, leaderboard.page_count, page_word));
+
+        return description;
+    }
+
+    // Add users to the list
+    for farmer in &leaderboard.entries {
+        // Top 3 get special medals, ties included
+        match farmer.rank {
+            1 => description.push_str("`` 🥇 ``"),
+            2 => description.push_str("`` 🥈 ``"),
+            3 => description.push_str("`` 🥉 ``"),
+            _ => description.push_str(&format!("`` {} ``",farmer.rank))
+        }
+
+        // Fall back to the user id for accounts Discord can't find
+        match profiles.get(&farmer.user_id) {
+            Some(profile) => {
+                description.push_str(&profile.display_name);
+            }
+            None => {
+                description.push_str(&farmer.user_id.to_string())
+            }
+        }
+
+        let paw_word = if farmer.count != 1 {"paws"} else {"paw"};
+        description.push_str(&format!(" - {} {}\n",farmer.count,paw_word));
+    }
+
+    let remaining = leaderboard.farmers_after();
+    if remaining > 0 {
+        let farmer_word = if remaining != 1 {"farmers"} else {"farmer"};
+        description.push_str(&format!("``...`` {} other {}\n",remaining,farmer_word));
+    }
+
+    match standing {
+        Some(standing) => {
+            let paw_word = if standing.count != 1 {"paws"} else {"paw"};
+            description.push_str(&format!("`` {} `` {} - {} {}",standing.rank,display_name(ctx.author()), standing.count, paw_word));
+        }
+        None => description.push_str(&format!("`` - `` {} - unranked",display_name(ctx.author())))
+    }
+
+    description
+}
@@ -0,0 +1,472 @@
+mod admin;
+mod cooldowns;
+mod duels;
+mod games;
+mod history;
+mod leaderboard;
+mod reminders;
+mod roles;
+mod settings;
+mod shop;
+use crate::helpers::{daily, durations::{format_until, Locale}, outcome::{Outcome, OutcomeResolver, WeightedOutcomes}, structs::{Cooldown, ItemEffect, LedgerReason, ReminderAction, ServerSettings, Transfer, TransferResult, OPERATOR_ID}, AppState};
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude as serenity;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, AppState, Error>;
+use ::serenity::all::Mentionable;
+use serenity::builder::CreateEmbed;
+use poise::reply::CreateReply;
+use rand::thread_rng;
+use admin::admin;
+use cooldowns::cooldowns;
+use duels::duel;
+use games::{blackjack, coinflip, dice, gamble, slots};
+use history::history;
+use leaderboard::top;
+use reminders::{queue_reminder, remind};
+use roles::{buy_role, roles, roles_admin};
+use settings::config;
+use shop::{buy, inventory, shop, shop_admin};
+
+
+fn get_guild_id(ctx: Context<'_>) -> Result<u64, Error> {
+    let id = match ctx.guild_id() {
+        Some(guild_id) => guild_id.get(), // GuildId has a .0 field which is the u64 representation
+        None => return Err(Error::from("Guild ID not found")), // Handle the error case as needed
+    };
+    Ok(id)  
+}
+
+// Who made a change, operators on the command line have no account to mention
+fn actor_mention(actor_id: u64) -> String {
+    match actor_id {
+        OPERATOR_ID => "an operator".to_string(),
+        actor_id => format!("<@{}>", actor_id)
+    }
+}
+
+// How long until a moment, in the server's duration style. Only messages written
+// out in every locale should pass the caller's, the rest pass English.
+fn format_wait(settings: &ServerSettings, until: DateTime<Utc>, now: DateTime<Utc>, locale: Locale) -> String {
+    format_until(until, now, settings.duration_style, locale)
+}
+
+// The caller's Discord language, for the messages that are written out in it
+fn locale(ctx: Context<'_>) -> Locale {
+    Locale::from_discord(ctx.locale())
+}
+
+// A gift from the caller, the transfer refuses to overdraw them
+fn give_transfer(guild_id: u64, caller_id: u64, target_id: u64, count: u64) -> Transfer {
+    Transfer {
+        server_id: guild_id,
+        actor_id: caller_id,
+        source_id: Some(caller_id),
+        target_id: Some(target_id),
+        amount: count,
+        reason: LedgerReason::Give
+    }
+}
+
+// Successes take from the target, failures pay the target, and nobody can go below
+// zero. Balances are the ones read before the steal.
+fn steal_transfer(guild_id: u64, caller_id: u64, target_id: u64, outcome: Outcome, count: u64, caller_paws: u64, target_paws: u64) -> Transfer {
+    let settlement = outcome.settle(count, count);
+    let amount = if outcome.is_success() {
+        std::cmp::min(settlement as u64, target_paws)
+    } else {
+        std::cmp::min(settlement.unsigned_abs(), caller_paws)
+    };
+
+    Transfer {
+        server_id: guild_id,
+        actor_id: caller_id,
+        source_id: Some(if outcome.is_success() {target_id} else {caller_id}),
+        target_id: Some(if outcome.is_success() {caller_id} else {target_id}),
+        amount,
+        reason: match outcome {
+            Outcome::Failure => LedgerReason::StealFailed,
+            Outcome::Caught => LedgerReason::StealCaught,
+            _ => LedgerReason::Steal
+        }
+    }
+}
+
+// Result embed for gambles and steals, with a dog for every paw held
+fn outcome_embed(title: &str, mut description: String, new_paws: u64, won: bool) -> CreateEmbed {
+    let dogs = "🐶".repeat(std::cmp::min(396,new_paws as usize));
+    description.push_str(&dogs);
+    description.push(if won {'📈'} else {'📉'});
+
+    CreateEmbed::new()
+        .title(title)
+        .description(description)
+}
+
+#[poise::command(prefix_command, slash_command, subcommands("balance","history","daily","cooldowns","remind","steal","top","gamble","coinflip","dice","slots","blackjack","give","duel","config","admin","shop","buy","inventory","shop_admin","roles","buy_role","roles_admin"))]
+pub async fn paw(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Claims your daily paw drop, bigger for every day in a row"), prefix_command)]
+pub async fn daily(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+    let settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let streak = ctx.data().store.get_daily_streak(user_id, guild_id).await?;
+    let now = Utc::now();
+
+    // Users can only collect paws daily
+    let claim = match daily::claim(&settings, user_id, guild_id, &streak, now) {
+        Ok(claim) => claim,
+        Err(available_at) => {
+            ctx.send(CreateReply::default()
+                .content(match locale(ctx) {
+                    Locale::English => format!("You already claimed your daily paw! Come back {}", format_wait(&settings, available_at, now, Locale::English)),
+                    Locale::German => format!("Du hast deine tägliche Pfote schon abgeholt! Komm {} wieder", format_wait(&settings, available_at, now, Locale::German)),
+                    Locale::French => format!("Tu as déjà récupéré ta patte du jour ! Reviens {}", format_wait(&settings, available_at, now, Locale::French)),
+                    Locale::Spanish => format!("¡Ya reclamaste tu pata diaria! Vuelve {}", format_wait(&settings, available_at, now, Locale::Spanish))
+                })
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    // Someone else claimed for this user between reading the streak and paying out
+    let Some(paw_count) = ctx.data().store.claim_daily(&claim).await? else {
+        ctx.send(CreateReply::default()
+            .content("You already claimed your daily paw!")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    };
+    queue_reminder(ctx, guild_id, ReminderAction::Daily, daily::next_claim_at(&settings, now), now).await;
+
+    let mut message = if claim.reward != 1 {
+        format!("You claimed {} daily paws, and now hold onto {} paws!", claim.reward, paw_count)
+    } else {
+        format!("You claimed your daily paw, and now hold onto {} paws!", paw_count)
+    };
+
+    let day_word = if claim.streak != 1 {"days"} else {"day"};
+    message.push_str(&format!("\n🔥 {} {} in a row", claim.streak, day_word));
+
+    if claim.streak == 1 && streak.current > 1 {
+        message.push_str(&format!(", your {} day streak ran out", streak.current));
+    } else if claim.streak == claim.best && claim.streak > 1 {
+        message.push_str(", your best streak yet");
+    }
+
+    let next_reward = daily::reward(&settings, claim.streak + 1);
+    if next_reward > claim.reward {
+        message.push_str(&format!("\nCome back {} for {} paws", format_wait(&settings, daily::next_claim_at(&settings, now), now, Locale::English), next_reward));
+    }
+
+    ctx.reply(message).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Donate paws to others"), prefix_command)]
+pub async fn give(
+    ctx: Context<'_>,
+    who: serenity::User,
+    count: u32
+) -> Result<(), Error> {
+    // Users cannot target themselves
+    if who == *ctx.author() {
+        ctx.send(CreateReply::default()
+            .content("You cannot donate to yourself")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if count == 0 {
+        ctx.send(CreateReply::default()
+            .content("You have to give at least one paw")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let caller_id = ctx.author().id.get();
+    let target_id = who.id.get();
+    let guild_id = get_guild_id(ctx)?;
+
+    // Move the paws in one go
+    let transfer = give_transfer(guild_id, caller_id, target_id, count as u64);
+    if let TransferResult::InsufficientFunds { .. } = ctx.data().store.transfer_paws(&transfer).await? {
+        ctx.send(CreateReply::default()
+            .content("You can only give as many paws as you have!")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let paw_word = if count != 1 {"paws"} else {"paw"};
+    ctx.reply(format!("You gave {} {} to {}, how nice of you!",count,paw_word,who.mention())).await?;
+
+    Ok(())
+}
+
+
+#[poise::command(slash_command, description_localized("en-US","tells you how many paws you have"), prefix_command)]
+pub async fn balance(
+    ctx: Context<'_>,
+    #[description = "(optional) member to check the balance of"] 
+    who: Option<serenity::User>
+) -> Result<(), Error> {
+    let target = if let Some(target) = who {
+        target
+    } else {
+        ctx.author().clone()
+    };
+
+    let user_id = target.id.get();
+    let guild_id = get_guild_id(ctx)?;
+    let paw_count = ctx.data().store.get_paw_count(user_id, guild_id).await?;
+    let settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let streak = ctx.data().store.get_daily_streak(user_id, guild_id).await?;
+    let avatar_url = match target.avatar_url() {
+        Some(avatar_url) => avatar_url,
+        None => "".to_string()
+    };
+
+    let mut description = format!("{} has {} paws.",target.mention(),paw_count);
+
+    // Streaks that ran out show as zero until the next claim resets them
+    if streak.best > 0 {
+        let current = daily::active_streak(&settings, &streak, Utc::now());
+        let day_word = if current != 1 {"days"} else {"day"};
+        description.push_str(&format!("\n🔥 Daily streak: {} {} (best {})", current, day_word, streak.best));
+    }
+
+    let embed = CreateEmbed::new() 
+        .title("🐶 paw count 🐶")
+        .description(description)
+        .thumbnail(avatar_url);
+    
+    ctx.send(CreateReply::default()
+        .embed(embed)
+    ).await?;
+    
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","If you're lucky, you might be able to steal some."), prefix_command)]
+pub async fn steal(
+    ctx: Context<'_>,
+    who: serenity::User,
+    count: u64
+) -> Result<(), Error> {
+    if who == *ctx.author() {
+        ctx.send(CreateReply::default()
+            .content("You cannot steal from yourself")
+            .ephemeral(true)
+        ).await?;
+
+        return Ok(());
+    }
+
+    if count == 0 {
+        ctx.send(CreateReply::default()
+            .content("You have to steal at least one paw")
+            .ephemeral(true)
+        ).await?;
+
+        return Ok(());
+    }
+
+    let caller_user_id = ctx.author().id.get();
+    let target_user_id = who.id.get();
+    let guild_id = get_guild_id(ctx)?;
+
+    let caller_paw_count = ctx.data().store.get_paw_count(caller_user_id, guild_id).await?;
+    if caller_paw_count < count {
+        ctx.send(CreateReply::default()
+            .content("You can only steal as many paws as you have!")
+            .ephemeral(true)
+        ).await?;
+
+        return Ok(());
+    }
+
+    let target_paw_count = ctx.data().store.get_paw_count(target_user_id, guild_id).await?;
+    if target_paw_count < count {
+        ctx.send(CreateReply::default()
+            .content("That user doesnt have that many paws!")
+            .ephemeral(true)
+        ).await?;
+
+        return Ok(());
+    }
+
+    let server_settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let last_stole = ctx.data().store.get_last_used(caller_user_id, guild_id, Cooldown::Steal).await?;
+    let now = Utc::now();
+    
+    // Limit how often someone can steal
+    let available_at = Cooldown::Steal.available_at(&server_settings, last_stole);
+    if let Some(available_at) = available_at.filter(|available_at| now < *available_at) {
+        ctx.send(CreateReply::default()
+            .content(match locale(ctx) {
+                Locale::English => format!("🚫 🐶 stealing addiction is a serious problem. Regulations require a wait. Try again {}.", format_wait(&server_settings, available_at, now, Locale::English)),
+                Locale::German => format!("🚫 🐶 Klausucht ist ein ernstes Problem. Die Vorschriften verlangen eine Pause. Versuch es {} noch einmal.", format_wait(&server_settings, available_at, now, Locale::German)),
+                Locale::French => format!("🚫 🐶 l'addiction au vol est un problème sérieux. La réglementation impose une pause. Réessaie {}.", format_wait(&server_settings, available_at, now, Locale::French)),
+                Locale::Spanish => format!("🚫 🐶 la adicción a robar es un problema serio. Las normas exigen una espera. Vuelve a intentarlo {}.", format_wait(&server_settings, available_at, now, Locale::Spanish))
+            })
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    // Starting the cooldown is what lets the steal go ahead, so only one of several
+    // sent at once gets through
+    if !ctx.data().store.claim_cooldown(caller_user_id, guild_id, Cooldown::Steal, last_stole, now).await? {
+        ctx.send(CreateReply::default()
+            .content("🚫 🐶 stealing addiction is a serious problem. You're already stealing, wait for it to finish.")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+    queue_reminder(ctx, guild_id, ReminderAction::Steal, now + server_settings.steal_interval.duration(), now).await;
+
+    // A shield stops the steal outright, the attempt still counts towards the cooldown
+    if let Some(shield) = ctx.data().store.consume_item(target_user_id, guild_id, ItemEffect::StealShield).await? {
+        let paw_word = if caller_paw_count != 1 {"paws"} else {"paw"};
+        let description = format!("{} was protected by a {}, your thievery bounced right off. You still have {} {}.", who.mention(), shield.name, caller_paw_count, paw_word);
+
+        ctx.send(CreateReply::default()
+            .embed(outcome_embed("🛡️ 🐶 🛡️", description, caller_paw_count, false))
+        ).await?;
+
+        return Ok(());
+    }
+
+    // Let the server's odds decide how it went
+    let outcome = WeightedOutcomes::for_steal(&server_settings)?.resolve(&mut thread_rng());
+
+    let transfer = steal_transfer(guild_id, caller_user_id, target_user_id, outcome, count, caller_paw_count, target_paw_count);
+    let amount = transfer.amount;
+
+    // Balances may have changed since the checks above, the transfer has the final say
+    let new_paws = match ctx.data().store.transfer_paws(&transfer).await? {
+        TransferResult::Completed { source_count, target_count } => {
+            if outcome.is_success() {target_count} else {source_count}.unwrap_or(0)
+        }
+        TransferResult::InsufficientFunds { .. } => {
+            let content = if outcome.is_success() {"That user doesnt have that many paws!"} else {"You can only steal as many paws as you have!"};
+            ctx.send(CreateReply::default()
+                .content(content)
+                .ephemeral(true)
+            ).await?;
+
+            return Ok(());
+        }
+    };
+
+    let amount_paw_word = if amount != 1 {"paws"} else {"paw"};
+    let new_paw_word = if new_paws != 1 {"paws"} else {"paw"};
+
+    let description = match outcome {
+        Outcome::CriticalSuccess => format!("Your thievery went perfectly, you stole {} {} from {}, giving you a total of {} {}.", amount, amount_paw_word, who.mention(), new_paws, new_paw_word),
+        Outcome::Success => format!("Your thievery paid off, you stole {} {} from {}, giving you a total of {} {}.", amount, amount_paw_word, who.mention(), new_paws, new_paw_word),
+        Outcome::PartialSuccess => format!("Your thievery was sloppy, you only stole {} {} from {}, giving you a total of {} {}.", amount, amount_paw_word, who.mention(), new_paws, new_paw_word),
+        Outcome::Failure => format!("Your thievery sucked, you gave {} {} to {}, giving you a total of {} {}.", amount, amount_paw_word, who.mention(), new_paws, new_paw_word),
+        Outcome::Caught => format!("You got caught red-handed and were fined {} {} in favour of {}, giving you a total of {} {}.", amount, amount_paw_word, who.mention(), new_paws, new_paw_word)
+    };
+
+    ctx.send(CreateReply::default()
+        .embed(outcome_embed("🧤 🐶 🧤", description, new_paws, outcome.is_success()))
+    ).await?;
+
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::store::{memory::MemoryStore, PawStore};
+
+    async fn store_with(balances: &[(u64, u64)]) -> MemoryStore {
+        let store = MemoryStore::default();
+        for (user_id, count) in balances {
+            store.set_paw_count(*user_id, 1, 9, *count).await.unwrap();
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn gives() {
+        let store = store_with(&[(1, 10)]).await;
+
+        let given = store.transfer_paws(&give_transfer(1, 1, 2, 4)).await.unwrap();
+        assert_eq!(given, TransferResult::Completed { source_count: Some(6), target_count: Some(4) });
+
+        // Giving more than you have moves nothing
+        let refused = store.transfer_paws(&give_transfer(1, 1, 2, 7)).await.unwrap();
+        assert_eq!(refused, TransferResult::InsufficientFunds { available: 6 });
+        assert_eq!((store.get_paw_count(1, 1).await.unwrap(), store.get_paw_count(2, 1).await.unwrap()), (6, 4));
+    }
+
+    #[tokio::test]
+    async fn steals() {
+        // Outcome, balances after, and how the ledger files it
+        let cases = [
+            (Outcome::CriticalSuccess, 14, 6, LedgerReason::Steal),
+            (Outcome::Success, 12, 8, LedgerReason::Steal),
+            (Outcome::PartialSuccess, 11, 9, LedgerReason::Steal),
+            (Outcome::Failure, 8, 12, LedgerReason::StealFailed),
+            (Outcome::Caught, 6, 14, LedgerReason::StealCaught)
+        ];
+
+        for (outcome, caller_paws, target_paws, reason) in cases {
+            let store = store_with(&[(1, 10), (2, 10)]).await;
+            let transfer = steal_transfer(1, 1, 2, outcome, 2, 10, 10);
+            assert_eq!(transfer.reason, reason);
+
+            store.transfer_paws(&transfer).await.unwrap();
+            assert_eq!(store.get_paw_count(1, 1).await.unwrap(), caller_paws, "{:?}", outcome);
+            assert_eq!(store.get_paw_count(2, 1).await.unwrap(), target_paws, "{:?}", outcome);
+        }
+
+        // Nobody is taken below zero
+        let store = store_with(&[(1, 3), (2, 1)]).await;
+        store.transfer_paws(&steal_transfer(1, 1, 2, Outcome::CriticalSuccess, 1, 3, 1)).await.unwrap();
+        assert_eq!(store.get_paw_count(2, 1).await.unwrap(), 0);
+        store.transfer_paws(&steal_transfer(1, 1, 2, Outcome::Caught, 3, 4, 0)).await.unwrap();
+        assert_eq!((store.get_paw_count(1, 1).await.unwrap(), store.get_paw_count(2, 1).await.unwrap()), (0, 4));
+
+        // Balances that changed since they were read turn the steal away
+        let store = store_with(&[(1, 10), (2, 1)]).await;
+        let stale = steal_transfer(1, 1, 2, Outcome::Success, 5, 10, 10);
+        assert_eq!(store.transfer_paws(&stale).await.unwrap(), TransferResult::InsufficientFunds { available: 1 });
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn cooldown_races() {
+        let store = MemoryStore::default();
+        let now = Utc::now();
+
+        // Two steals sent at once both read no previous steal, only one goes ahead
+        let (first, second) = tokio::join!(
+            store.claim_cooldown(1, 1, Cooldown::Steal, None, now),
+            store.claim_cooldown(1, 1, Cooldown::Steal, None, now)
+        );
+        assert!(first.unwrap() ^ second.unwrap());
+
+        // The next one has to have seen that steal
+        let later = now + chrono::Duration::hours(1);
+        assert!(!store.claim_cooldown(1, 1, Cooldown::Steal, None, later).await.unwrap());
+        let last = store.get_last_used(1, 1, Cooldown::Steal).await.unwrap();
+        assert_eq!(last, Some(now));
+        assert!(store.claim_cooldown(1, 1, Cooldown::Steal, last, later).await.unwrap());
+
+        // Cooldowns don't share a claim
+        assert!(store.claim_cooldown(1, 1, Cooldown::Gamble, None, later).await.unwrap());
+    }
+}
@@ -0,0 +1,95 @@
+use super::{format_wait, get_guild_id, Context, Error};
+use crate::helpers::{daily, durations::Locale, structs::{Reminder, ReminderAction, ReminderDelivery, ServerSettings}};
+use chrono::{DateTime, Utc};
+use poise::reply::CreateReply;
+use tracing::log::warn;
+
+fn action_phrase(action: ReminderAction) -> &'static str {
+    match action {
+        ReminderAction::Daily => "your daily is ready",
+        ReminderAction::Gamble => "you can gamble again",
+        ReminderAction::Steal => "you can steal again"
+    }
+}
+
+// When an action is next ready for the caller, None if it already is
+pub async fn ready_at(ctx: Context<'_>, settings: &ServerSettings, guild_id: u64, action: ReminderAction, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, Error> {
+    let user_id = ctx.author().id.get();
+
+    let available_at = match action.cooldown() {
+        Some(cooldown) => cooldown.available_at(settings, ctx.data().store.get_last_used(user_id, guild_id, cooldown).await?),
+        None => ctx.data().store.get_daily_streak(user_id, guild_id).await?
+            .last_claimed
+            .map(|last_claimed| daily::next_claim_at(settings, last_claimed))
+    };
+
+    Ok(available_at.filter(|available_at| now < *available_at))
+}
+
+// Lines up a reminder for when an action the caller just used is ready again. The
+// store skips members who didn't opt in, and the action already went through, so
+// failing here is only logged.
+pub async fn queue_reminder(ctx: Context<'_>, guild_id: u64, action: ReminderAction, due_at: DateTime<Utc>, now: DateTime<Utc>) {
+    if due_at <= now {
+        return;
+    }
+
+    if let Err(error) = ctx.data().store.schedule_reminder(guild_id, ctx.author().id.get(), action, due_at).await {
+        warn!("Couldn't schedule a {} reminder: {:?}", action.as_str(), error);
+    }
+}
+
+#[poise::command(slash_command, description_localized("en-US","Get reminded when your daily, gamble or steal is ready again"), prefix_command)]
+pub async fn remind(
+    ctx: Context<'_>,
+    #[description = "what to be reminded about"]
+    action: ReminderAction,
+    #[description = "(optional) by dm or with a ping in this channel, leave out to stop reminders"]
+    delivery: Option<ReminderDelivery>
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+
+    let Some(delivery) = delivery else {
+        let content = if ctx.data().store.delete_reminder(guild_id, user_id, action).await? {
+            format!("You won't be reminded when {} anymore", action_phrase(action))
+        } else {
+            format!("You weren't being reminded when {}", action_phrase(action))
+        };
+
+        ctx.send(CreateReply::default()
+            .content(content)
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    };
+
+    let settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+    let now = Utc::now();
+
+    // An action that is still cooling down gets its first reminder straight away
+    let due_at = ready_at(ctx, &settings, guild_id, action, now).await?;
+    ctx.data().store.upsert_reminder(&Reminder {
+        server_id: guild_id,
+        user_id,
+        action,
+        delivery,
+        channel_id: Some(ctx.channel_id().get()).filter(|_| delivery == ReminderDelivery::Channel),
+        due_at
+    }).await?;
+
+    let mut content = match delivery {
+        ReminderDelivery::Dm => format!("I'll DM you whenever {}", action_phrase(action)),
+        ReminderDelivery::Channel => format!("I'll ping you in this channel whenever {}", action_phrase(action))
+    };
+    if let Some(due_at) = due_at {
+        content.push_str(&format!(", starting {}", format_wait(&settings, due_at, now, Locale::English)));
+    }
+
+    ctx.send(CreateReply::default()
+        .content(content)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
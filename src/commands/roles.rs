@@ -0,0 +1,295 @@
+use super::{get_guild_id, Context, Error};
+use crate::helpers::structs::{RoleListing, TransferResult};
+use ::serenity::all::Mentionable;
+use chrono::Utc;
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use serenity::builder::CreateEmbed;
+use serenity::model::id::RoleId;
+use serenity::Permissions;
+
+// Roles that can moderate or run the server aren't for sale, since anyone with
+// enough paws could buy them
+const ELEVATED_PERMISSIONS: Permissions = Permissions::ADMINISTRATOR
+    .union(Permissions::MANAGE_GUILD)
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::MANAGE_CHANNELS)
+    .union(Permissions::MANAGE_MESSAGES)
+    .union(Permissions::MANAGE_THREADS)
+    .union(Permissions::MANAGE_WEBHOOKS)
+    .union(Permissions::MANAGE_NICKNAMES)
+    .union(Permissions::MANAGE_GUILD_EXPRESSIONS)
+    .union(Permissions::MANAGE_EVENTS)
+    .union(Permissions::KICK_MEMBERS)
+    .union(Permissions::BAN_MEMBERS)
+    .union(Permissions::MODERATE_MEMBERS)
+    .union(Permissions::MUTE_MEMBERS)
+    .union(Permissions::DEAFEN_MEMBERS)
+    .union(Permissions::MOVE_MEMBERS)
+    .union(Permissions::MENTION_EVERYONE)
+    .union(Permissions::VIEW_AUDIT_LOG);
+
+fn listing_terms(listing: &RoleListing) -> String {
+    let paw_word = if listing.price != 1 {"paws"} else {"paw"};
+
+    match listing.duration_days {
+        Some(days) => format!("{} {} for {} {}", listing.price, paw_word, days, if days != 1 {"days"} else {"day"}),
+        None => format!("{} {} to keep", listing.price, paw_word)
+    }
+}
+
+#[poise::command(slash_command, description_localized("en-US","Shows the roles paws can buy on this server"), prefix_command)]
+pub async fn roles(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let listings = ctx.data().store.get_role_listings(guild_id).await?;
+    let rentals = ctx.data().store.get_role_rentals(guild_id, ctx.author().id.get()).await?;
+
+    let mut description = "".to_string();
+    if listings.is_empty() {
+        description.push_str("No roles are for sale yet 🌵 Admins can list one with /paw roles_admin sell_role");
+    }
+
+    for listing in &listings {
+        description.push_str(&format!("{} - {}", RoleId::new(listing.role_id).mention(), listing_terms(listing)));
+
+        if let Some(rental) = rentals.iter().find(|rental| rental.role_id == listing.role_id) {
+            description.push_str(&format!(", yours until <t:{}:f>", rental.expires_at.timestamp()));
+        }
+        description.push('\n');
+    }
+
+    let embed = CreateEmbed::new()
+        .title("🎖️ Roles for sale 🎖️")
+        .description(description);
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_bot_permissions = "MANAGE_ROLES",
+    description_localized("en-US","Buy a role with paws")
+)]
+pub async fn buy_role(
+    ctx: Context<'_>,
+    #[description = "role to buy"]
+    role: serenity::Role
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+
+    let listings = ctx.data().store.get_role_listings(guild_id).await?;
+    let Some(listing) = listings.into_iter().find(|listing| listing.role_id == role.id.get()) else {
+        ctx.send(CreateReply::default()
+            .content(format!("{} isn't for sale, take a look at /paw roles", role.mention()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    };
+
+    // The role may have been given more permissions since it was listed
+    if role.permissions.intersects(ELEVATED_PERMISSIONS) {
+        ctx.send(CreateReply::default()
+            .content(format!("{} can't be bought while it has moderator permissions, ask an admin to unlist it", role.mention()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let member = ctx.author_member().await.ok_or("Couldn't find the member buying a role")?;
+    let had_role = member.roles.contains(&role.id);
+    let rentals = ctx.data().store.get_role_rentals(guild_id, user_id).await?;
+    let renting = rentals.iter().any(|rental| rental.role_id == listing.role_id);
+
+    // Rentals can be extended, but a role held for good can't be bought again
+    if had_role && !renting {
+        ctx.send(CreateReply::default()
+            .content(format!("You already have {}", role.mention()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let paw_count = ctx.data().store.get_paw_count(user_id, guild_id).await?;
+    if paw_count < listing.price {
+        ctx.send(CreateReply::default()
+            .content(format!("{} costs {} and you only have {}", role.mention(), listing_terms(&listing), paw_count))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    // Hand the role over first, so nobody pays for a role the bot isn't allowed to give
+    if !had_role {
+        let added = ctx.http().add_member_role(role.guild_id, member.user.id, role.id, Some("Bought with paws")).await;
+        if added.is_err() {
+            ctx.send(CreateReply::default()
+                .content(format!("I'm not allowed to give out {}, ask an admin to move my role above it", role.mention()))
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    }
+
+    let new_paws = match ctx.data().store.buy_role(user_id, &listing, Utc::now()).await? {
+        TransferResult::Completed { source_count, .. } => source_count.unwrap_or(0),
+        TransferResult::InsufficientFunds { available } => {
+            if !had_role {
+                ctx.http().remove_member_role(role.guild_id, member.user.id, role.id, Some("Couldn't pay for the role")).await?;
+            }
+
+            ctx.send(CreateReply::default()
+                .content(format!("{} costs {} and you only have {}", role.mention(), listing_terms(&listing), available))
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    let rental = ctx.data().store.get_role_rentals(guild_id, user_id).await?
+        .into_iter()
+        .find(|rental| rental.role_id == listing.role_id);
+
+    let paw_word = if new_paws != 1 {"paws"} else {"paw"};
+    let mut description = format!("You bought {} for {}, leaving you with {} {}.", role.mention(), listing_terms(&listing), new_paws, paw_word);
+    if let Some(rental) = rental {
+        description.push_str(&format!(" It's yours until <t:{}:f>.", rental.expires_at.timestamp()));
+    }
+
+    let embed = CreateEmbed::new()
+        .title("🎖️ 🐶 🎖️")
+        .description(description);
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("sell_role","unlist_role"),
+    description_localized("en-US","Sell roles for paws")
+)]
+pub async fn roles_admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    required_permissions = "MANAGE_ROLES",
+    required_bot_permissions = "MANAGE_ROLES",
+    description_localized("en-US","Puts a role up for sale, or changes its price")
+)]
+pub async fn sell_role(
+    ctx: Context<'_>,
+    #[description = "role to sell"]
+    role: serenity::Role,
+    #[description = "price in paws"]
+    #[min = 1]
+    price: u64,
+    #[description = "(optional) days the role lasts, forever if left out"]
+    #[min = 1]
+    #[max = 365]
+    days: Option<u32>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+
+    // @everyone shares the server's id, and managed roles belong to integrations
+    if role.id.get() == guild_id || role.managed {
+        ctx.send(CreateReply::default()
+            .content(format!("{} can't be sold", role.mention()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if role.permissions.intersects(ELEVATED_PERMISSIONS) {
+        ctx.send(CreateReply::default()
+            .content(format!("{} can't be sold, it has moderator or admin permissions", role.mention()))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    // Sellers can only list roles they could hand out themselves, and the bot can
+    // only give out roles below its own
+    let guild = ctx.partial_guild().await.ok_or("Couldn't find the server to sell a role on")?;
+    let seller = ctx.author_member().await.ok_or("Couldn't find the member selling a role")?;
+    let bot_id = ctx.cache().current_user().id;
+    let bot = guild.id.member(ctx, bot_id).await?;
+    let highest_role = |member: &serenity::Member| member.roles.iter().filter_map(|id| guild.roles.get(id)).max().cloned();
+
+    let refusal = if guild.owner_id != seller.user.id && highest_role(&seller).is_none_or(|highest| role >= highest) {
+        Some(format!("You can only sell roles below your own highest role, and {} isn't", role.mention()))
+    } else if highest_role(&bot).is_none_or(|highest| role >= highest) {
+        Some(format!("I can only give out roles below my own, ask an admin to move my role above {}", role.mention()))
+    } else {
+        None
+    };
+    if let Some(refusal) = refusal {
+        ctx.send(CreateReply::default()
+            .content(refusal)
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    if price == 0 || price > i64::MAX as u64 || days.is_some_and(|days| !(1..=365).contains(&days)) {
+        ctx.send(CreateReply::default()
+            .content("Roles need a price of at least 1 paw, and can be rented for 1 to 365 days")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let listing = RoleListing {
+        server_id: guild_id,
+        role_id: role.id.get(),
+        price,
+        duration_days: days.map(|days| days as i32)
+    };
+    ctx.data().store.upsert_role_listing(&listing).await?;
+
+    ctx.send(CreateReply::default()
+        .content(format!("{} is now for sale at {}", role.mention(), listing_terms(&listing)))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Stops selling a role, anyone renting it keeps it until it runs out"), prefix_command)]
+pub async fn unlist_role(
+    ctx: Context<'_>,
+    #[description = "role to stop selling"]
+    role: serenity::Role
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+
+    let content = if ctx.data().store.delete_role_listing(guild_id, role.id.get()).await? {
+        format!("{} is no longer for sale", role.mention())
+    } else {
+        format!("{} wasn't for sale", role.mention())
+    };
+
+    ctx.send(CreateReply::default()
+        .content(content)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use super::{get_guild_id, Context, Error};
+use crate::helpers::{settings::{apply_setting, check_settings, SettingKey}, stakes, structs::ServerSettings, types::MyDuration};
+use poise::reply::CreateReply;
+use poise::ChoiceParameter;
+use poise::serenity_prelude as serenity;
+use serenity::builder::CreateEmbed;
+
+fn minutes_word(duration: &MyDuration) -> String {
+    let minutes = duration.duration().num_minutes();
+    let minute_word = if minutes != 1 {"minutes"} else {"minute"};
+
+    format!("{} {}", minutes, minute_word)
+}
+
+fn percent_or_off(percent: Option<i32>) -> String {
+    match percent {
+        Some(percent) => format!("{}%", percent),
+        None => "off".to_string()
+    }
+}
+
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("view","set","reset"),
+    description_localized("en-US","Change how paws behave on this server")
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Shows the current server settings"), prefix_command)]
+pub async fn view(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+
+    let mut description = "".to_string();
+    description.push_str(&format!("⏱️ steal_interval: {}\n", minutes_word(&settings.steal_interval)));
+    description.push_str(&format!("⏱️ gamble_interval: {}\n", minutes_word(&settings.gamble_interval)));
+    description.push_str(&format!("🧤 steal_chance: {}%\n", settings.steal_chance));
+    description.push_str(&format!("🧤 steal_critical_chance: {}%\n", settings.steal_critical_chance));
+    description.push_str(&format!("🧤 steal_partial_chance: {}%\n", settings.steal_partial_chance));
+    description.push_str(&format!("🚨 steal_caught_chance: {}%\n", settings.steal_caught_chance));
+    description.push_str(&format!("🎲 gamble_chance: {}%\n", settings.gamble_chance));
+    description.push_str(&format!("🎲 gamble_critical_chance: {}%\n", settings.gamble_critical_chance));
+    description.push_str(&format!("🎲 gamble_partial_chance: {}%\n", settings.gamble_partial_chance));
+    description.push_str(&format!("🏆 ranking_mode: {}\n", settings.ranking_mode.as_str()));
+    description.push_str(&format!("📅 daily_reward: {}\n", settings.daily_reward));
+    description.push_str(&format!("📅 daily_streak_bonus: {}\n", settings.daily_streak_bonus));
+    description.push_str(&format!("📅 daily_streak_cap: {}\n", settings.daily_streak_cap));
+    description.push_str(&format!("📅 daily_grace: {}\n", minutes_word(&settings.daily_grace)));
+    description.push_str(&format!("📅 daily_reset: {}\n", settings.daily_reset.as_str()));
+    description.push_str(&format!("🌍 timezone: {}\n", settings.timezone.name()));
+    description.push_str(&format!("⏳ duration_style: {}\n", settings.duration_style.as_str()));
+    description.push_str(&format!("💰 gamble_min_stake: {}\n", settings.gamble_min_stake));
+    description.push_str(&format!("💰 gamble_max_stake: {}\n", settings.gamble_max_stake));
+    description.push_str(&format!("💰 gamble_max_stake_percent: {}\n", percent_or_off(settings.gamble_max_stake_percent)));
+    description.push_str(&format!("💰 gamble_payout_percent: {} (a win pays {:.2}x)", percent_or_off(settings.gamble_payout_percent), stakes::multiplier(&settings)));
+
+    let embed = CreateEmbed::new()
+        .title("⚙️ Server settings ⚙️")
+        .description(description);
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Changes a server setting"), prefix_command)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "setting to change"]
+    setting: SettingKey,
+    #[description = "minutes for intervals, paws for stakes, percent (or off) for chances, or a name like Europe/Berlin"]
+    value: String
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let mut settings = ctx.data().settings.get(ctx.data().store.as_ref(), guild_id).await?;
+
+    if let Err(reason) = apply_setting(&mut settings, setting, &value).and_then(|_| check_settings(&settings)) {
+        ctx.send(CreateReply::default()
+            .content(reason)
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    ctx.data().settings.set(ctx.data().store.as_ref(), guild_id, &settings).await?;
+
+    ctx.send(CreateReply::default()
+        .content(format!("{} is now set to {}", setting.name(), value))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+// Copies a single setting from the defaults
+fn reset_setting(settings: &mut ServerSettings, defaults: &ServerSettings, key: SettingKey) {
+    match key {
+        SettingKey::StealInterval => settings.steal_interval = defaults.steal_interval,
+        SettingKey::GambleInterval => settings.gamble_interval = defaults.gamble_interval,
+        SettingKey::StealChance => settings.steal_chance = defaults.steal_chance,
+        SettingKey::GambleChance => settings.gamble_chance = defaults.gamble_chance,
+        SettingKey::GambleCriticalChance => settings.gamble_critical_chance = defaults.gamble_critical_chance,
+        SettingKey::GamblePartialChance => settings.gamble_partial_chance = defaults.gamble_partial_chance,
+        SettingKey::StealCriticalChance => settings.steal_critical_chance = defaults.steal_critical_chance,
+        SettingKey::StealPartialChance => settings.steal_partial_chance = defaults.steal_partial_chance,
+        SettingKey::StealCaughtChance => settings.steal_caught_chance = defaults.steal_caught_chance,
+        SettingKey::RankingMode => settings.ranking_mode = defaults.ranking_mode,
+        SettingKey::DailyReward => settings.daily_reward = defaults.daily_reward,
+        SettingKey::DailyStreakBonus => settings.daily_streak_bonus = defaults.daily_streak_bonus,
+        SettingKey::DailyStreakCap => settings.daily_streak_cap = defaults.daily_streak_cap,
+        SettingKey::DailyGrace => settings.daily_grace = defaults.daily_grace,
+        SettingKey::DailyReset => settings.daily_reset = defaults.daily_reset,
+        SettingKey::Timezone => settings.timezone = defaults.timezone,
+        SettingKey::DurationStyle => settings.duration_style = defaults.duration_style,
+        SettingKey::GambleMinStake => settings.gamble_min_stake = defaults.gamble_min_stake,
+        SettingKey::GambleMaxStake => settings.gamble_max_stake = defaults.gamble_max_stake,
+        SettingKey::GambleMaxStakePercent => settings.gamble_max_stake_percent = defaults.gamble_max_stake_percent,
+        SettingKey::GamblePayoutPercent => settings.gamble_payout_percent = defaults.gamble_payout_percent
+    }
+}
+
+#[poise::command(slash_command, description_localized("en-US","Puts the server settings back to their defaults"), prefix_command)]
+pub async fn reset(
+    ctx: Context<'_>,
+    #[description = "(optional) only reset this setting"]
+    setting: Option<SettingKey>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let settings_cache = &ctx.data().settings;
+
+    let content = if let Some(setting) = setting {
+        let mut settings = settings_cache.get(ctx.data().store.as_ref(), guild_id).await?;
+        reset_setting(&mut settings, settings_cache.defaults(), setting);
+
+        // The default may not fit alongside the other chances or stakes
+        if let Err(reason) = check_settings(&settings) {
+            ctx.send(CreateReply::default()
+                .content(format!("{} can't be reset: {}", setting.name(), reason))
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+
+        settings_cache.set(ctx.data().store.as_ref(), guild_id, &settings).await?;
+
+        format!("{} has been reset to the default", setting.name())
+    } else if settings_cache.reset(ctx.data().store.as_ref(), guild_id).await? {
+        "Server settings have been reset to the defaults".to_string()
+    } else {
+        "This server is already using the default settings".to_string()
+    };
+
+    ctx.send(CreateReply::default()
+        .content(content)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use super::{get_guild_id, Context, Error};
+use crate::helpers::structs::{ItemEffect, ShopItem, TransferResult};
+use poise::reply::CreateReply;
+use poise::serenity_prelude as serenity;
+use serenity::builder::CreateEmbed;
+
+// What an item does, in words
+pub fn effect_description(item: &ShopItem) -> String {
+    match item.effect {
+        ItemEffect::StealShield => "🛡️ blocks the next steal against you".to_string(),
+        ItemEffect::GambleCharm => format!("🍀 +{}% to win your next gamble", item.strength)
+    }
+}
+
+// Exact names win, otherwise names are matched ignoring case
+fn find_item(items: Vec<ShopItem>, name: &str) -> Option<ShopItem> {
+    let name = name.trim();
+    let position = items.iter().position(|item| item.name == name)
+        .or_else(|| items.iter().position(|item| item.name.eq_ignore_ascii_case(name)))?;
+
+    items.into_iter().nth(position)
+}
+
+async fn autocomplete_item(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Ok(guild_id) = get_guild_id(ctx) else {
+        return Vec::new();
+    };
+
+    let items = ctx.data().store.get_shop_items(guild_id).await.unwrap_or_default();
+    let partial = partial.to_lowercase();
+
+    items.into_iter()
+        .map(|item| item.name)
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(25) // Discord shows at most 25 choices
+        .collect()
+}
+
+#[poise::command(slash_command, description_localized("en-US","Browse what paws can buy on this server"), prefix_command)]
+pub async fn shop(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let items = ctx.data().store.get_shop_items(guild_id).await?;
+
+    let mut description = "".to_string();
+    if items.is_empty() {
+        description.push_str("Nothing is for sale yet 🌵 Admins can stock the shop with /paw shop_admin add_item");
+    }
+
+    for item in &items {
+        let paw_word = if item.price != 1 {"paws"} else {"paw"};
+        description.push_str(&format!("**{}** - {} {}\n", item.name, item.price, paw_word));
+        if !item.description.is_empty() {
+            description.push_str(&format!("{}\n", item.description));
+        }
+        description.push_str(&format!("{}\n\n", effect_description(item)));
+    }
+
+    let embed = CreateEmbed::new()
+        .title("🛒 Paw shop 🛒")
+        .description(description);
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Spend your paws in the shop"), prefix_command)]
+pub async fn buy(
+    ctx: Context<'_>,
+    #[description = "item to buy"]
+    #[autocomplete = "autocomplete_item"]
+    item: String,
+    #[description = "(optional) how many to buy"]
+    #[min = 1]
+    #[max = 100]
+    quantity: Option<u32>
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.get();
+    let guild_id = get_guild_id(ctx)?;
+    let quantity = quantity.unwrap_or(1);
+
+    if !(1..=100).contains(&quantity) {
+        ctx.send(CreateReply::default()
+            .content("You can buy between 1 and 100 at a time")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let items = ctx.data().store.get_shop_items(guild_id).await?;
+    let Some(item) = find_item(items, &item) else {
+        ctx.send(CreateReply::default()
+            .content(format!("The shop doesn't sell \"{}\", take a look at /paw shop", item))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    };
+
+    let total = item.price.saturating_mul(quantity as u64);
+    let total_paw_word = if total != 1 {"paws"} else {"paw"};
+
+    let new_paws = match ctx.data().store.buy_item(user_id, &item, quantity as u64).await? {
+        TransferResult::Completed { source_count, .. } => source_count.unwrap_or(0),
+        TransferResult::InsufficientFunds { available } => {
+            let available_paw_word = if available != 1 {"paws"} else {"paw"};
+            ctx.send(CreateReply::default()
+                .content(format!("That costs {} {} and you only have {} {}", total, total_paw_word, available, available_paw_word))
+                .ephemeral(true)
+            ).await?;
+            return Ok(());
+        }
+    };
+
+    let new_paw_word = if new_paws != 1 {"paws"} else {"paw"};
+    let embed = CreateEmbed::new()
+        .title("🛒 🐶 🛒")
+        .description(format!("You bought {}x {} for {} {}, leaving you with {} {}.\n{}", quantity, item.name, total, total_paw_word, new_paws, new_paw_word, effect_description(&item)));
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Shows the items you're holding"), prefix_command)]
+pub async fn inventory(
+    ctx: Context<'_>,
+    #[description = "(optional) member to check the inventory of"]
+    who: Option<serenity::User>
+) -> Result<(), Error> {
+    let target = who.as_ref().unwrap_or(ctx.author());
+    let guild_id = get_guild_id(ctx)?;
+    let inventory = ctx.data().store.get_inventory(target.id.get(), guild_id).await?;
+
+    let mut description = "".to_string();
+    if inventory.is_empty() {
+        description.push_str("Nothing but dog hair 🌵");
+    }
+
+    for held in &inventory {
+        description.push_str(&format!("**{}x {}**\n{}\n", held.quantity, held.item.name, effect_description(&held.item)));
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("🎒 {}'s inventory 🎒", target.name))
+        .description(description);
+
+    ctx.send(CreateReply::default()
+        .embed(embed)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("add_item","remove_item"),
+    description_localized("en-US","Stock the shop")
+)]
+pub async fn shop_admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Adds an item to the shop, or changes one with the same name"), prefix_command)]
+pub async fn add_item(
+    ctx: Context<'_>,
+    #[description = "item name"]
+    #[max_length = 50]
+    name: String,
+    #[description = "price in paws"]
+    #[min = 1]
+    price: u64,
+    #[description = "what the item does"]
+    effect: ItemEffect,
+    #[description = "(optional) percent added to the odds, for charms"]
+    #[min = 1]
+    #[max = 100]
+    strength: Option<u32>,
+    #[description = "(optional) flavour text"]
+    #[max_length = 200]
+    description: Option<String>
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let name = name.trim().to_string();
+
+    let strength = match effect {
+        ItemEffect::GambleCharm => strength.unwrap_or(10),
+        ItemEffect::StealShield => 0
+    };
+
+    if name.is_empty() || price == 0 || strength > 100 || price > i64::MAX as u64 {
+        ctx.send(CreateReply::default()
+            .content("Items need a name, a price of at least 1 paw, and a strength of at most 100%")
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    // Keep the existing spelling so a differently cased name updates the same item
+    let items = ctx.data().store.get_shop_items(guild_id).await?;
+    let name = find_item(items, &name).map(|item| item.name).unwrap_or(name);
+
+    let item = ctx.data().store.upsert_shop_item(&ShopItem {
+        id: 0,
+        server_id: guild_id,
+        name,
+        description: description.unwrap_or_default(),
+        price,
+        effect,
+        strength: strength as i32
+    }).await?;
+
+    let paw_word = if item.price != 1 {"paws"} else {"paw"};
+    ctx.send(CreateReply::default()
+        .content(format!("{} is now for sale at {} {}, {}", item.name, item.price, paw_word, effect_description(&item)))
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, description_localized("en-US","Takes an item out of the shop, along with every one still held"), prefix_command)]
+pub async fn remove_item(
+    ctx: Context<'_>,
+    #[description = "item name"]
+    #[autocomplete = "autocomplete_item"]
+    name: String
+) -> Result<(), Error> {
+    let guild_id = get_guild_id(ctx)?;
+    let items = ctx.data().store.get_shop_items(guild_id).await?;
+
+    let removed = match find_item(items, &name) {
+        Some(item) => ctx.data().store.delete_shop_item(guild_id, &item.name).await?.then_some(item.name),
+        None => None
+    };
+
+    let content = match removed {
+        Some(name) => format!("{} has been taken out of the shop", name),
+        None => format!("The shop doesn't sell \"{}\"", name)
+    };
+
+    ctx.send(CreateReply::default()
+        .content(content)
+        .ephemeral(true)
+    ).await?;
+
+    Ok(())
+}
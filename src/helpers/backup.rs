@@ -0,0 +1,449 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use chrono::{DateTime, Utc};
+use poise::ChoiceParameter;
+use serde::{Deserialize, Deserializer, Serialize};
+use super::settings::{apply_setting, check_settings, setting_value, SettingKey};
+use super::structs::{EconomyData, ImportMode, ServerSettings, UserLimits, MAX_PAWS};
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum BackupFormat {
+    #[name = "json"]
+    Json,
+    #[name = "csv"]
+    Csv
+}
+
+impl BackupFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupFormat::Json => "json",
+            BackupFormat::Csv => "csv"
+        }
+    }
+
+    // Going by the extension, for files that don't say
+    pub fn from_file_name(name: &str) -> Option<BackupFormat> {
+        let (_, extension) = name.rsplit_once('.')?;
+        extension.to_ascii_lowercase().parse().ok()
+    }
+}
+
+impl std::str::FromStr for BackupFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(BackupFormat::Json),
+            "csv" => Ok(BackupFormat::Csv),
+            _ => Err(format!("Unknown backup format \"{}\", use json or csv", value))
+        }
+    }
+}
+
+// A server's economy as it's written to a file. Settings are kept the way
+// /paw config set takes them, so imports check them the same way.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EconomyBackup {
+    #[serde(default)]
+    pub paw_count: Vec<BalanceRecord>,
+    #[serde(default)]
+    pub user_limits: Vec<LimitsRecord>,
+    // Empty when the server uses the defaults
+    #[serde(default, deserialize_with = "settings_as_text")]
+    pub server_settings: BTreeMap<String, String>
+}
+
+// Exports write every setting as text, but a number is fine in a file written by hand
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SettingValue {
+    Text(String),
+    Number(serde_json::Number)
+}
+
+fn settings_as_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error> {
+    let settings = BTreeMap::<String, SettingValue>::deserialize(deserializer)?;
+
+    Ok(settings.into_iter()
+        .map(|(name, value)| match value {
+            SettingValue::Text(text) => (name, text),
+            SettingValue::Number(number) => (name, number.to_string())
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceRecord {
+    pub user_id: u64,
+    pub count: u64
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitsRecord {
+    pub user_id: u64,
+    #[serde(default)]
+    pub last_steal: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_daily: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_gamble: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub daily_streak: u32,
+    #[serde(default)]
+    pub best_daily_streak: u32
+}
+
+// CSV has one row per value: the table it belongs to, whose it is (empty for
+// settings), and which column
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    table: String,
+    user_id: Option<u64>,
+    field: String,
+    value: String
+}
+
+impl EconomyBackup {
+    pub fn from_data(data: &EconomyData) -> EconomyBackup {
+        EconomyBackup {
+            paw_count: data.balances.iter()
+                .map(|(user_id, count)| BalanceRecord { user_id: *user_id, count: *count })
+                .collect(),
+            user_limits: data.limits.iter()
+                .map(|(user_id, limits)| LimitsRecord {
+                    user_id: *user_id,
+                    last_steal: limits.last_steal,
+                    last_daily: limits.last_daily,
+                    last_gamble: limits.last_gamble,
+                    daily_streak: limits.daily_streak as u32,
+                    best_daily_streak: limits.best_daily_streak as u32
+                })
+                .collect(),
+            server_settings: data.settings.as_ref()
+                .map(|settings| (0..).map_while(SettingKey::from_index)
+                    .map(|key| (key.name().to_string(), setting_value(settings, key)))
+                    .collect())
+                .unwrap_or_default()
+        }
+    }
+
+    // Checks every row and turns the backup into what gets imported, or lists
+    // everything wrong with it. Settings go on top of what the server has when
+    // merging and on top of the defaults when replacing.
+    pub fn to_data(&self, mode: ImportMode, current: &ServerSettings, defaults: &ServerSettings) -> Result<EconomyData, Vec<String>> {
+        let mut problems = Vec::new();
+
+        let mut seen = HashSet::new();
+        for balance in &self.paw_count {
+            if !seen.insert(balance.user_id) {
+                problems.push(format!("paw_count: {} is listed more than once", balance.user_id));
+            }
+            if balance.count > MAX_PAWS {
+                problems.push(format!("paw_count: {} has more paws than can be stored", balance.user_id));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for limits in &self.user_limits {
+            if !seen.insert(limits.user_id) {
+                problems.push(format!("user_limits: {} is listed more than once", limits.user_id));
+            }
+            if limits.best_daily_streak > i32::MAX as u32 {
+                problems.push(format!("user_limits: {} has a longer streak than can be stored", limits.user_id));
+            }
+            if limits.daily_streak > limits.best_daily_streak {
+                problems.push(format!("user_limits: {} has a daily_streak above their best_daily_streak", limits.user_id));
+            }
+        }
+
+        let settings = match (self.server_settings.is_empty(), mode) {
+            (true, _) => None,
+            (false, ImportMode::Merge) => Some(current.clone()),
+            (false, ImportMode::Replace) => Some(defaults.clone())
+        };
+        let settings = settings.map(|mut settings| {
+            let before = problems.len();
+            for (name, value) in &self.server_settings {
+                let applied = SettingKey::from_name(name)
+                    .ok_or_else(|| format!("there's no setting called {}", name))
+                    .and_then(|key| apply_setting(&mut settings, key, value));
+                if let Err(reason) = applied {
+                    problems.push(format!("server_settings: {}", reason));
+                }
+            }
+
+            // Only worth checking together once each of them is fine
+            if problems.len() == before {
+                if let Err(reason) = check_settings(&settings) {
+                    problems.push(format!("server_settings: {}", reason));
+                }
+            }
+            settings
+        });
+
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        Ok(EconomyData {
+            balances: self.paw_count.iter().map(|balance| (balance.user_id, balance.count)).collect(),
+            limits: self.user_limits.iter()
+                .map(|limits| (limits.user_id, UserLimits {
+                    last_steal: limits.last_steal,
+                    last_daily: limits.last_daily,
+                    last_gamble: limits.last_gamble,
+                    daily_streak: limits.daily_streak as i32,
+                    best_daily_streak: limits.best_daily_streak as i32
+                }))
+                .collect(),
+            settings
+        })
+    }
+
+    pub fn write(&self, format: BackupFormat) -> Result<Vec<u8>, Error> {
+        match format {
+            BackupFormat::Json => {
+                let mut bytes = serde_json::to_vec_pretty(self)?;
+                bytes.push(b'\n');
+                Ok(bytes)
+            }
+            BackupFormat::Csv => self.write_csv()
+        }
+    }
+
+    pub fn read(bytes: &[u8], format: BackupFormat) -> Result<EconomyBackup, Error> {
+        match format {
+            BackupFormat::Json => Ok(serde_json::from_slice(bytes)?),
+            BackupFormat::Csv => EconomyBackup::read_csv(bytes)
+        }
+    }
+
+    fn write_csv(&self) -> Result<Vec<u8>, Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let row = |table: &str, user_id: Option<u64>, field: &str, value: String| CsvRow {
+            table: table.to_string(),
+            user_id,
+            field: field.to_string(),
+            value
+        };
+
+        for balance in &self.paw_count {
+            writer.serialize(row("paw_count", Some(balance.user_id), "count", balance.count.to_string()))?;
+        }
+
+        for limits in &self.user_limits {
+            let user_id = Some(limits.user_id);
+            let timestamps = [("last_steal", limits.last_steal), ("last_daily", limits.last_daily), ("last_gamble", limits.last_gamble)];
+
+            // Never used is left out rather than written empty
+            for (field, time) in timestamps {
+                if let Some(time) = time {
+                    writer.serialize(row("user_limits", user_id, field, time.to_rfc3339()))?;
+                }
+            }
+            writer.serialize(row("user_limits", user_id, "daily_streak", limits.daily_streak.to_string()))?;
+            writer.serialize(row("user_limits", user_id, "best_daily_streak", limits.best_daily_streak.to_string()))?;
+        }
+
+        for (name, value) in &self.server_settings {
+            writer.serialize(row("server_settings", None, name, value.clone()))?;
+        }
+
+        Ok(writer.into_inner().map_err(|error| error.into_error())?)
+    }
+
+    fn read_csv(bytes: &[u8]) -> Result<EconomyBackup, Error> {
+        let mut backup = EconomyBackup::default();
+        // Limits are spread over several rows, this is where each member's went
+        let mut limits_index: HashMap<u64, usize> = HashMap::new();
+
+        let mut reader = csv::Reader::from_reader(bytes);
+        for (index, row) in reader.deserialize::<CsvRow>().enumerate() {
+            let row = row?;
+            // The header is line 1
+            let line = index + 2;
+            let invalid = |what: &str| format!("line {}: {} isn't a valid {}", line, row.value, what);
+
+            match (row.table.as_str(), row.user_id) {
+                ("paw_count", Some(user_id)) if row.field == "count" => {
+                    let count = row.value.trim().parse().map_err(|_| invalid("number of paws"))?;
+                    backup.paw_count.push(BalanceRecord { user_id, count });
+                }
+                ("user_limits", Some(user_id)) => {
+                    let index = *limits_index.entry(user_id).or_insert_with(|| {
+                        backup.user_limits.push(LimitsRecord { user_id, ..Default::default() });
+                        backup.user_limits.len() - 1
+                    });
+                    let record = &mut backup.user_limits[index];
+
+                    let time = || DateTime::parse_from_rfc3339(row.value.trim())
+                        .map(|time| time.with_timezone(&Utc))
+                        .map_err(|_| invalid("timestamp"));
+                    let streak = || row.value.trim().parse::<u32>().map_err(|_| invalid("streak"));
+
+                    match row.field.as_str() {
+                        "last_steal" => record.last_steal = Some(time()?),
+                        "last_daily" => record.last_daily = Some(time()?),
+                        "last_gamble" => record.last_gamble = Some(time()?),
+                        "daily_streak" => record.daily_streak = streak()?,
+                        "best_daily_streak" => record.best_daily_streak = streak()?,
+                        field => return Err(format!("line {}: user_limits has no {} column", line, field).into())
+                    }
+                }
+                ("server_settings", None) => {
+                    backup.server_settings.insert(row.field.clone(), row.value.clone());
+                }
+                (table, _) => {
+                    return Err(format!("line {}: don't know what to do with {} {}", line, table, row.field).into());
+                }
+            }
+        }
+
+        Ok(backup)
+    }
+}
+
+// What an import brings in, as told to whoever runs it
+pub fn describe_import(data: &EconomyData, mode: ImportMode) -> String {
+    let paws = data.balances.iter().fold(0, |paws: u64, (_, count)| paws.saturating_add(*count));
+    let settings = match (&data.settings, mode) {
+        (Some(_), _) => "the server settings",
+        (None, ImportMode::Merge) => "no settings",
+        (None, ImportMode::Replace) => "the default settings"
+    };
+
+    format!(
+        "{} {} holding {} {}, {} cooldown and streak {} and {}",
+        data.balances.len(), if data.balances.len() != 1 {"balances"} else {"balance"},
+        paws, if paws != 1 {"paws"} else {"paw"},
+        data.limits.len(), if data.limits.len() != 1 {"records"} else {"record"},
+        settings
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn backup() -> EconomyBackup {
+        EconomyBackup {
+            paw_count: vec![BalanceRecord { user_id: 1, count: 40 }, BalanceRecord { user_id: 2, count: 0 }],
+            user_limits: vec![LimitsRecord {
+                user_id: 1,
+                last_daily: Some(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()),
+                daily_streak: 3,
+                best_daily_streak: 5,
+                ..Default::default()
+            }],
+            server_settings: BTreeMap::from([("gamble_chance".to_string(), "40".to_string())])
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for format in [BackupFormat::Json, BackupFormat::Csv] {
+            let bytes = backup().write(format).unwrap();
+            assert_eq!(EconomyBackup::read(&bytes, format).unwrap(), backup());
+        }
+    }
+
+    #[test]
+    fn reads_csv() {
+        let csv = "table,user_id,field,value\npaw_count,1,count,12\nuser_limits,1,daily_streak,2\nserver_settings,,timezone,Europe/Berlin\n";
+        let read = EconomyBackup::read(csv.as_bytes(), BackupFormat::Csv).unwrap();
+        assert_eq!(read.paw_count, [BalanceRecord { user_id: 1, count: 12 }]);
+        assert_eq!(read.user_limits[0].daily_streak, 2);
+        assert_eq!(read.server_settings["timezone"], "Europe/Berlin");
+
+        let csv = "table,user_id,field,value\npaw_count,1,count,12\npaw_count,2,count,lots\n";
+        let error = EconomyBackup::read(csv.as_bytes(), BackupFormat::Csv).unwrap_err();
+        assert_eq!(error.to_string(), "line 3: lots isn't a valid number of paws");
+
+        let csv = "table,user_id,field,value\nuser_limits,1,last_nap,2\n";
+        assert!(EconomyBackup::read(csv.as_bytes(), BackupFormat::Csv).is_err());
+        assert!(EconomyBackup::read(b"{\"paw_count\": 5}", BackupFormat::Json).is_err());
+    }
+
+    #[test]
+    fn reads_json_numbers() {
+        let json = br#"{"server_settings": {"gamble_chance": 40, "gamble_max_stake_percent": "off", "timezone": "Europe/Berlin"}}"#;
+        let read = EconomyBackup::read(json, BackupFormat::Json).unwrap();
+        assert_eq!(read.server_settings["gamble_chance"], "40");
+        assert_eq!(read.server_settings["gamble_max_stake_percent"], "off");
+        assert_eq!(read.server_settings["timezone"], "Europe/Berlin");
+
+        assert!(EconomyBackup::read(br#"{"server_settings": {"gamble_chance": true}}"#, BackupFormat::Json).is_err());
+    }
+
+    #[test]
+    fn settings_go_on_top_of_the_mode() {
+        let current = ServerSettings { steal_chance: 70, ..ServerSettings::default() };
+        let defaults = ServerSettings::default();
+
+        let merged = backup().to_data(ImportMode::Merge, &current, &defaults).unwrap();
+        let merged = merged.settings.unwrap();
+        assert_eq!((merged.gamble_chance, merged.steal_chance), (40, 70));
+
+        let replaced = backup().to_data(ImportMode::Replace, &current, &defaults).unwrap();
+        let replaced = replaced.settings.unwrap();
+        assert_eq!((replaced.gamble_chance, replaced.steal_chance), (40, 50));
+
+        let no_settings = EconomyBackup { server_settings: BTreeMap::new(), ..backup() };
+        assert_eq!(no_settings.to_data(ImportMode::Replace, &current, &defaults).unwrap().settings, None);
+    }
+
+    #[test]
+    fn lists_every_problem() {
+        let mut bad = backup();
+        bad.paw_count.push(BalanceRecord { user_id: 1, count: u64::MAX });
+        bad.user_limits.push(LimitsRecord { user_id: 3, daily_streak: 4, best_daily_streak: 2, ..Default::default() });
+        bad.server_settings.insert("luck".to_string(), "7".to_string());
+        bad.server_settings.insert("gamble_min_stake".to_string(), "lots".to_string());
+
+        let problems = bad.to_data(ImportMode::Merge, &ServerSettings::default(), &ServerSettings::default()).unwrap_err();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert!(problems.contains(&"paw_count: 1 is listed more than once".to_string()));
+        assert!(problems.contains(&"paw_count: 1 has more paws than can be stored".to_string()));
+        assert!(problems.contains(&"user_limits: 3 has a daily_streak above their best_daily_streak".to_string()));
+        assert!(problems.contains(&"server_settings: there's no setting called luck".to_string()));
+
+        // Settings that are fine on their own can still clash
+        let mut clashing = backup();
+        clashing.server_settings.insert("gamble_critical_chance".to_string(), "70".to_string());
+        assert_eq!(clashing.to_data(ImportMode::Replace, &ServerSettings::default(), &ServerSettings::default()).unwrap_err().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn imports_an_export() {
+        use crate::helpers::store::{memory::MemoryStore, PawStore};
+
+        let store = MemoryStore::default();
+        store.set_paw_count(1, 1, 9, 40).await.unwrap();
+        store.set_paw_count(2, 1, 9, 15).await.unwrap();
+        let settings = ServerSettings { gamble_chance: 35, ..ServerSettings::default() };
+        store.upsert_server_settings(1, &settings).await.unwrap();
+
+        // Exported from one server and replacing everything on another, in either format
+        for (server_id, format) in [(2, BackupFormat::Json), (3, BackupFormat::Csv)] {
+            let bytes = EconomyBackup::from_data(&store.export_economy(1).await.unwrap()).write(format).unwrap();
+            store.set_paw_count(5, server_id, 9, 3).await.unwrap();
+
+            let data = EconomyBackup::read(&bytes, format).unwrap()
+                .to_data(ImportMode::Replace, &ServerSettings::default(), &ServerSettings::default()).unwrap();
+            store.import_economy(server_id, 9, &data, ImportMode::Replace).await.unwrap();
+
+            let imported = store.export_economy(server_id).await.unwrap();
+            assert_eq!(imported.balances, [(1, 40), (2, 15)]);
+            assert_eq!(store.get_server_settings(server_id).await.unwrap(), Some(settings.clone()));
+        }
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(BackupFormat::from_file_name("paws-1.JSON"), Some(BackupFormat::Json));
+        assert_eq!(BackupFormat::from_file_name("paws.csv"), Some(BackupFormat::Csv));
+        assert_eq!(BackupFormat::from_file_name("paws.txt"), None);
+    }
+}
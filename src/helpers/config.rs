@@ -1,22 +1,337 @@
-use dotenv::dotenv;
+use std::collections::BTreeMap;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use poise::ChoiceParameter;
+use serde::Deserialize;
+use tracing::log::LevelFilter;
+use super::settings::{apply_setting, check_settings, SettingKey};
+use super::structs::ServerSettings;
 
-fn get_env_var(var_name: &str) -> String {
-    dotenv().ok();
-    env::var(var_name).unwrap_or_else(|_| "{} is not set".to_owned())
+// Read from the working directory when no other file is given, and fine to leave out
+pub const DEFAULT_CONFIG_FILE: &str = "paw-bot.toml";
+
+// What the config file may hold. Everything is optional since the environment
+// can fill in the rest, and anything misspelled is refused rather than ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    database_url: Option<String>,
+    discord_token: Option<String>,
+    log_level: Option<String>,
+    pool_size: Option<u32>,
+    profile_ttl_minutes: Option<i64>,
+    persist_profiles: Option<bool>,
+    owner_ids: Option<Vec<u64>>,
+    allowed_guilds: Option<Vec<u64>>,
+    // Same names and values as /paw config set
+    #[serde(default)]
+    defaults: BTreeMap<String, toml::Value>
 }
 
-#[derive(Debug, Clone)] 
+#[derive(Debug, Clone)]
 pub struct Config {
+    // The file that was read, if there was one
+    pub file: Option<PathBuf>,
     pub database_url: String,
-    pub discord_token: String,
+    // Only needed to run the bot, the other commands work without it
+    pub discord_token: Option<String>,
+    pub log_level: LevelFilter,
+    // Most database connections open at once
+    pub pool_size: u32,
+    // How long a cached Discord name or avatar is trusted
+    pub profile_ttl_minutes: i64,
+    // Whether cached profiles are also written to the database
+    pub persist_profiles: bool,
+    // Members who can use every command on every server
+    pub owner_ids: Vec<u64>,
+    // Servers the bot answers on, all of them when empty
+    pub allowed_guilds: Vec<u64>,
+    // Settings of every server that hasn't changed them
+    pub defaults: ServerSettings
 }
 
 impl Config {
-    pub fn init() -> Config {
-        Config {
-            database_url: get_env_var("DATABASE_URL"),
-            discord_token: get_env_var("DISCORD_TOKEN")
+    // Reads the config file, then lets the environment override it. The file is
+    // the one given, else PAW_CONFIG, else paw-bot.toml if there is one. Every
+    // problem found is returned at once so a deploy can be fixed in one go.
+    pub fn load(file: Option<&Path>) -> Result<Config, Vec<String>> {
+        let file = file.map(Path::to_path_buf)
+            .or_else(|| env_var("PAW_CONFIG").map(PathBuf::from))
+            .or_else(|| Path::new(DEFAULT_CONFIG_FILE).exists().then(|| PathBuf::from(DEFAULT_CONFIG_FILE)));
+        // Nothing else is worth checking against a file that couldn't be read
+        let contents = match &file {
+            Some(file) => read_file(file).map_err(|problem| vec![problem])?,
+            None => FileConfig::default()
+        };
+
+        let mut problems = Vec::new();
+        let database_url = env_var("DATABASE_URL").or(contents.database_url).filter(|url| !url.trim().is_empty());
+        let discord_token = env_var("DISCORD_TOKEN").or(contents.discord_token).filter(|token| !token.trim().is_empty());
+        let log_level = parse_env("LOG_LEVEL", "log level", &mut problems).or(contents.log_level);
+        let pool_size = parse_env("DATABASE_POOL_SIZE", "number of connections", &mut problems).or(contents.pool_size).unwrap_or(10);
+        let profile_ttl_minutes = parse_env("PROFILE_TTL_MINUTES", "number of minutes", &mut problems).or(contents.profile_ttl_minutes).unwrap_or(12 * 60);
+        let persist_profiles = parse_env_bool("PERSIST_PROFILES", &mut problems).or(contents.persist_profiles).unwrap_or(true);
+        let owner_ids = parse_env_ids("OWNER_IDS", &mut problems).or(contents.owner_ids).unwrap_or_default();
+        let allowed_guilds = parse_env_ids("ALLOWED_GUILDS", &mut problems).or(contents.allowed_guilds).unwrap_or_default();
+
+        let database_url = match database_url {
+            Some(url) => {
+                if let Err(problem) = check_database_url(&url) {
+                    problems.push(problem);
+                }
+                url
+            }
+            None => {
+                problems.push("database_url isn't set, put it in the config file or set DATABASE_URL".to_string());
+                String::new()
+            }
+        };
+
+        if let Some(token) = &discord_token {
+            if let Err(error) = poise::serenity_prelude::utils::validate_token(token) {
+                problems.push(format!("discord_token: {}", error));
+            }
+        }
+
+        let log_level = match log_level {
+            Some(level) => LevelFilter::from_str(level.trim()).unwrap_or_else(|_| {
+                problems.push(format!("log_level: \"{}\" isn't one of off, error, warn, info, debug or trace", level));
+                LevelFilter::Error
+            }),
+            // Same as env_logger shows without RUST_LOG
+            None => LevelFilter::Error
+        };
+
+        if !(1..=100).contains(&pool_size) {
+            problems.push(format!("pool_size: has to be between 1 and 100, not {}", pool_size));
+        }
+        if profile_ttl_minutes < 0 {
+            problems.push(format!("profile_ttl_minutes: can't be negative, not {}", profile_ttl_minutes));
+        }
+        if owner_ids.contains(&0) {
+            problems.push("owner_ids: 0 isn't a Discord user".to_string());
+        }
+        if allowed_guilds.contains(&0) {
+            problems.push("allowed_guilds: 0 isn't a Discord server".to_string());
+        }
+
+        let defaults = load_defaults(contents.defaults, &mut problems);
+
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        Ok(Config {
+            file,
+            database_url,
+            discord_token,
+            log_level,
+            pool_size,
+            profile_ttl_minutes,
+            persist_profiles,
+            owner_ids,
+            allowed_guilds,
+            defaults
+        })
+    }
+
+    pub fn is_guild_allowed(&self, guild_id: u64) -> bool {
+        self.allowed_guilds.is_empty() || self.allowed_guilds.contains(&guild_id)
+    }
+}
+
+fn read_file(file: &Path) -> Result<FileConfig, String> {
+    let text = std::fs::read_to_string(file).map_err(|error| format!("Couldn't read {}: {}", file.display(), error))?;
+    toml::from_str(&text).map_err(|error| format!("{} isn't valid: {}", file.display(), error.to_string().trim_end()))
+}
+
+fn env_var(var_name: &str) -> Option<String> {
+    env::var(var_name).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn parse_env<T: FromStr>(var_name: &str, what: &str, problems: &mut Vec<String>) -> Option<T> {
+    let value = env_var(var_name)?;
+    value.trim().parse().map_err(|_| problems.push(format!("{}: \"{}\" isn't a valid {}", var_name, value, what))).ok()
+}
+
+fn parse_env_bool(var_name: &str, problems: &mut Vec<String>) -> Option<bool> {
+    let value = env_var(var_name)?;
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => {
+            problems.push(format!("{}: \"{}\" isn't true or false", var_name, value));
+            None
+        }
+    }
+}
+
+// Comma separated, like OWNER_IDS=123,456
+fn parse_env_ids(var_name: &str, problems: &mut Vec<String>) -> Option<Vec<u64>> {
+    let value = env_var(var_name)?;
+    value.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| id.parse().map_err(|_| format!("{}: \"{}\" isn't a Discord id", var_name, id)))
+        .collect::<Result<Vec<u64>, String>>()
+        .map_err(|problem| problems.push(problem))
+        .ok()
+}
+
+fn check_database_url(url: &str) -> Result<(), String> {
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") || url.starts_with("memory:") {
+        return Ok(());
+    }
+
+    if url.starts_with("sqlite:") {
+        return match cfg!(feature = "sqlite") {
+            true => Ok(()),
+            false => Err("database_url points to SQLite but the bot was built without the sqlite feature".to_string())
+        };
+    }
+
+    Err("database_url has to start with postgres://, sqlite: or memory:".to_string())
+}
+
+// The [defaults] table, with PAW_DEFAULT_<SETTING> on top, checked the same way
+// /paw config set checks them
+fn load_defaults(values: BTreeMap<String, toml::Value>, problems: &mut Vec<String>) -> ServerSettings {
+    let mut defaults = ServerSettings::default();
+    let before = problems.len();
+
+    let mut values: BTreeMap<String, (String, String)> = values.into_iter()
+        .filter_map(|(name, value)| {
+            let value = match value {
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                _ => {
+                    problems.push(format!("defaults.{}: has to be a number or a string", name));
+                    return None;
+                }
+            };
+            Some((name.clone(), (format!("defaults.{}", name), value)))
+        })
+        .collect();
+
+    for key in (0..).map_while(SettingKey::from_index) {
+        let var_name = format!("PAW_DEFAULT_{}", key.name().to_ascii_uppercase());
+        if let Some(value) = env_var(&var_name) {
+            values.insert(key.name().to_string(), (var_name, value));
+        }
+    }
+
+    for (name, (source, value)) in &values {
+        let applied = SettingKey::from_name(name)
+            .ok_or_else(|| format!("there's no setting called {}", name))
+            .and_then(|key| apply_setting(&mut defaults, key, value));
+        if let Err(reason) = applied {
+            problems.push(format!("{}: {}", source, reason));
         }
     }
-}
\ No newline at end of file
+
+    // Only worth checking together once each of them is fine
+    if problems.len() == before {
+        if let Err(reason) = check_settings(&defaults) {
+            problems.push(format!("defaults: {}", reason));
+        }
+    }
+
+    defaults
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A config file of its own for each test, since they run side by side
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let file = env::temp_dir().join(format!("paw-bot-{}-{}.toml", std::process::id(), name));
+        std::fs::write(&file, contents).unwrap();
+        file
+    }
+
+    fn defaults(values: &[(&str, toml::Value)]) -> Result<ServerSettings, Vec<String>> {
+        let mut problems = Vec::new();
+        let values = values.iter().map(|(name, value)| (name.to_string(), value.clone())).collect();
+        let defaults = load_defaults(values, &mut problems);
+        if problems.is_empty() {Ok(defaults)} else {Err(problems)}
+    }
+
+    #[test]
+    fn reads_files() {
+        let file = config_file("good", "database_url = \"memory:\"\nowner_ids = [1, 2]\n\n[defaults]\ngamble_chance = 40\n");
+        let read = read_file(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(read.database_url.as_deref(), Some("memory:"));
+        assert_eq!(read.owner_ids, Some(vec![1, 2]));
+        assert_eq!(read.defaults["gamble_chance"], toml::Value::Integer(40));
+
+        // Misspelled keys are refused rather than ignored
+        let file = config_file("unknown", "databse_url = \"memory:\"\n");
+        let problem = read_file(&file).unwrap_err();
+        std::fs::remove_file(&file).unwrap();
+        assert!(problem.contains("unknown field `databse_url`"));
+    }
+
+    #[test]
+    fn missing_file() {
+        let problems = Config::load(Some(Path::new("/nonexistent/paw-bot.toml"))).unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Couldn't read /nonexistent/paw-bot.toml"));
+    }
+
+    #[test]
+    fn database_urls() {
+        assert!(check_database_url("postgres://localhost/paws").is_ok());
+        assert!(check_database_url("postgresql://localhost/paws").is_ok());
+        assert!(check_database_url("memory:").is_ok());
+        assert_eq!(check_database_url("sqlite://paws.db").is_ok(), cfg!(feature = "sqlite"));
+        assert!(check_database_url("mysql://localhost/paws").is_err());
+    }
+
+    #[test]
+    fn default_settings() {
+        let loaded = defaults(&[
+            ("gamble_chance", toml::Value::Integer(40)),
+            ("timezone", toml::Value::String("Europe/Berlin".to_string()))
+        ]).unwrap();
+        assert_eq!(loaded.gamble_chance, 40);
+        assert_eq!(loaded.timezone.name(), "Europe/Berlin");
+
+        let problems = defaults(&[
+            ("luck", toml::Value::Integer(7)),
+            ("gamble_chance", toml::Value::Boolean(true))
+        ]).unwrap_err();
+        assert_eq!(problems, [
+            "defaults.gamble_chance: has to be a number or a string",
+            "defaults.luck: there's no setting called luck"
+        ]);
+
+        // Only checked together once each of them is fine
+        let problems = defaults(&[("gamble_min_stake", toml::Value::Integer(50))]).unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("defaults: gamble_min_stake (50)"));
+    }
+
+    #[test]
+    fn guild_allow_list() {
+        let mut config = Config {
+            file: None,
+            database_url: "memory:".to_string(),
+            discord_token: None,
+            log_level: LevelFilter::Error,
+            pool_size: 10,
+            profile_ttl_minutes: 0,
+            persist_profiles: false,
+            owner_ids: vec![],
+            allowed_guilds: vec![],
+            defaults: ServerSettings::default()
+        };
+        assert!(config.is_guild_allowed(5));
+
+        config.allowed_guilds = vec![1, 2];
+        assert!(config.is_guild_allowed(2));
+        assert!(!config.is_guild_allowed(5));
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use super::structs::{DailyClaim, DailyReset, DailyStreak, ServerSettings};
+
+// Accepts IANA names like Europe/Berlin, ignoring case
+pub fn parse_timezone(value: &str) -> Result<Tz, String> {
+    let value = value.trim();
+
+    TZ_VARIANTS.iter()
+        .find(|timezone| timezone.name().eq_ignore_ascii_case(value))
+        .copied()
+        .ok_or_else(|| format!("Unknown timezone \"{}\", use a name like Europe/Berlin", value))
+}
+
+// First moment of a day in the timezone. A few places skip midnight when daylight
+// saving starts, so their day starts at the first hour that exists.
+fn start_of_day(timezone: Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+
+    (0..24)
+        .find_map(|hour| timezone.from_local_datetime(&(midnight + Duration::hours(hour))).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+// Start of the day after the one a moment falls on, in the server's timezone
+fn start_of_next_day(timezone: Tz, moment: DateTime<Utc>) -> DateTime<Utc> {
+    let date = moment.with_timezone(&timezone).date_naive();
+    start_of_day(timezone, date.succ_opt().unwrap_or(NaiveDate::MAX))
+}
+
+// When the daily after one claimed at last_claimed opens up
+pub fn next_claim_at(settings: &ServerSettings, last_claimed: DateTime<Utc>) -> DateTime<Utc> {
+    match settings.daily_reset {
+        DailyReset::Rolling => last_claimed + Duration::days(1),
+        DailyReset::Calendar => start_of_next_day(settings.timezone, last_claimed)
+    }
+}
+
+// Streaks survive as long as the next daily is claimed within the grace window after
+// it was due: when it opens for rolling resets, or when that whole day ends for calendar ones
+pub fn streak_breaks_at(settings: &ServerSettings, last_claimed: DateTime<Utc>) -> DateTime<Utc> {
+    let due = match settings.daily_reset {
+        DailyReset::Rolling => next_claim_at(settings, last_claimed),
+        DailyReset::Calendar => start_of_next_day(settings.timezone, next_claim_at(settings, last_claimed))
+    };
+
+    due + settings.daily_grace.duration()
+}
+
+// The streak as it stands at now, zero once it has been broken
+pub fn active_streak(settings: &ServerSettings, streak: &DailyStreak, now: DateTime<Utc>) -> u32 {
+    match streak.last_claimed {
+        Some(last_claimed) if now <= streak_breaks_at(settings, last_claimed) => streak.current,
+        _ => 0
+    }
+}
+
+// Paws paid on a given day of a streak. Each day adds the bonus until the cap is reached.
+pub fn reward(settings: &ServerSettings, streak: u32) -> u64 {
+    let growth = std::cmp::min(streak, settings.daily_streak_cap.max(1) as u32).saturating_sub(1);
+
+    settings.daily_reward.max(0) as u64 + settings.daily_streak_bonus.max(0) as u64 * growth as u64
+}
+
+// Works out what claiming now pays, or when the next daily opens if it's too early
+pub fn claim(settings: &ServerSettings, user_id: u64, server_id: u64, streak: &DailyStreak, now: DateTime<Utc>) -> Result<DailyClaim, DateTime<Utc>> {
+    if let Some(last_claimed) = streak.last_claimed {
+        let available_at = next_claim_at(settings, last_claimed);
+        if now < available_at {
+            return Err(available_at);
+        }
+    }
+
+    let current = active_streak(settings, streak, now) + 1;
+
+    Ok(DailyClaim {
+        user_id,
+        server_id,
+        previous: streak.last_claimed,
+        claimed_at: now,
+        streak: current,
+        best: std::cmp::max(streak.best, current),
+        reward: reward(settings, current)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap()
+    }
+
+    fn streak(last_claimed: DateTime<Utc>, current: u32, best: u32) -> DailyStreak {
+        DailyStreak { last_claimed: Some(last_claimed), current, best }
+    }
+
+    #[test]
+    fn rolling_streaks() {
+        let settings = ServerSettings { daily_reward: 10, daily_streak_bonus: 5, daily_streak_cap: 3, ..ServerSettings::default() };
+
+        let first = claim(&settings, 1, 2, &DailyStreak::default(), at(1, 12)).unwrap();
+        assert_eq!((first.streak, first.best, first.reward, first.previous), (1, 1, 10, None));
+
+        // Too early, told when it opens
+        assert_eq!(claim(&settings, 1, 2, &streak(at(1, 12), 1, 1), at(2, 11)).unwrap_err(), at(2, 12));
+
+        let second = claim(&settings, 1, 2, &streak(at(1, 12), 1, 1), at(2, 12)).unwrap();
+        assert_eq!((second.streak, second.reward), (2, 15));
+
+        // The bonus stops growing at the cap
+        let capped = claim(&settings, 1, 2, &streak(at(1, 12), 5, 5), at(2, 12)).unwrap();
+        assert_eq!((capped.streak, capped.best, capped.reward), (6, 6, 20));
+
+        // Within the day of grace the streak goes on, after it starts over
+        assert_eq!(claim(&settings, 1, 2, &streak(at(1, 12), 4, 9), at(3, 12)).unwrap().streak, 5);
+        let broken = claim(&settings, 1, 2, &streak(at(1, 12), 4, 9), at(3, 13)).unwrap();
+        assert_eq!((broken.streak, broken.best, broken.reward), (1, 9, 10));
+    }
+
+    #[test]
+    fn calendar_days() {
+        let settings = ServerSettings {
+            daily_reset: DailyReset::Calendar,
+            timezone: parse_timezone("europe/berlin").unwrap(),
+            ..ServerSettings::default()
+        };
+
+        // 23:00 UTC is already the next day in Berlin
+        assert_eq!(next_claim_at(&settings, at(1, 12)), at(1, 22));
+        assert_eq!(next_claim_at(&settings, at(1, 23)), at(2, 22));
+
+        // The next day has to be claimed before it ends, plus the grace
+        assert_eq!(streak_breaks_at(&settings, at(1, 12)), at(3, 22));
+        assert_eq!(active_streak(&settings, &streak(at(1, 12), 3, 3), at(3, 21)), 3);
+        assert_eq!(active_streak(&settings, &streak(at(1, 12), 3, 3), at(3, 23)), 0);
+    }
+
+    #[test]
+    fn timezones() {
+        assert_eq!(parse_timezone(" America/New_York "), Ok(Tz::America__New_York));
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+}
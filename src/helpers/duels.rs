@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use chrono::Utc;
+use tracing::log::{info, warn};
+use super::store::PawStore;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// How long the target has to answer a duel
+pub const DUEL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+// How often duels are checked, the duel command refunds its own when they run out
+// so this only catches ones it couldn't
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Duels can only be answered through the bot that sent them, so every one still
+// open from before a restart is refunded. They all run out within DUEL_TIMEOUT.
+pub async fn refund_open_duels(store: &dyn PawStore) -> Result<usize, Error> {
+    let cutoff = Utc::now() + chrono::Duration::from_std(DUEL_TIMEOUT)?;
+    refund_expired_duels(store, cutoff).await
+}
+
+// Refunds duels that ran out, for as long as the bot is running
+pub async fn expire_duels(store: Arc<dyn PawStore>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = refund_expired_duels(store.as_ref(), Utc::now()).await {
+            warn!("Couldn't check for expired duels: {:?}", error);
+        }
+    }
+}
+
+async fn refund_expired_duels(store: &dyn PawStore, now: chrono::DateTime<Utc>) -> Result<usize, Error> {
+    let mut refunded = 0;
+
+    for duel in store.get_expired_duels(now).await? {
+        // Already fought or refunded if there's nothing to cancel
+        if store.cancel_duel(&duel).await?.is_some() {
+            info!("Refunded {} paws to {} for an unanswered duel in {}", duel.stake, duel.challenger_id, duel.server_id);
+            refunded += 1;
+        }
+    }
+
+    Ok(refunded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::store::memory::MemoryStore;
+    use crate::helpers::structs::{Duel, DuelResult, LedgerFilter, LedgerReason};
+
+    fn duel(expires_at: chrono::DateTime<Utc>) -> Duel {
+        Duel { id: 0, server_id: 1, challenger_id: 1, target_id: 2, stake: 10, expires_at }
+    }
+
+    #[tokio::test]
+    async fn escrow() {
+        let store = MemoryStore::default();
+        store.set_paw_count(1, 1, 9, 15).await.unwrap();
+        store.set_paw_count(2, 1, 9, 5).await.unwrap();
+        let now = Utc::now();
+
+        // The stake is held while the duel is open, and only once
+        let id = store.open_duel(&duel(now)).await.unwrap().unwrap();
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 5);
+        assert_eq!(store.open_duel(&duel(now)).await.unwrap(), None);
+
+        // A target who can't match it leaves the duel open
+        let open = Duel { id, ..duel(now) };
+        assert_eq!(store.accept_duel(&open, 2).await.unwrap(), DuelResult::InsufficientFunds { available: 5 });
+
+        store.set_paw_count(2, 1, 9, 12).await.unwrap();
+        assert_eq!(store.accept_duel(&open, 2).await.unwrap(), DuelResult::Fought { winner_count: 22, loser_count: 5 });
+        assert_eq!(store.accept_duel(&open, 1).await.unwrap(), DuelResult::Closed);
+        assert_eq!(store.cancel_duel(&open).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn refunds() {
+        let store = MemoryStore::default();
+        store.set_paw_count(1, 1, 9, 30).await.unwrap();
+        let now = Utc::now();
+
+        let declined = Duel { id: store.open_duel(&duel(now)).await.unwrap().unwrap(), ..duel(now) };
+        assert_eq!(store.cancel_duel(&declined).await.unwrap(), Some(30));
+        assert_eq!(store.cancel_duel(&declined).await.unwrap(), None);
+
+        // Only duels that ran out are refunded in the background
+        store.open_duel(&duel(now)).await.unwrap().unwrap();
+        store.open_duel(&duel(now + chrono::Duration::minutes(2))).await.unwrap().unwrap();
+        assert_eq!(refund_expired_duels(&store, now).await.unwrap(), 1);
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 20);
+
+        // A restart refunds the rest
+        assert_eq!(refund_open_duels(&store).await.unwrap(), 1);
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 30);
+
+        let refunds = store.get_ledger(1, LedgerFilter { user_id: None, reason: Some(LedgerReason::DuelRefund) }, 0, 10).await.unwrap();
+        assert_eq!(refunds.len(), 3);
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Duration, Utc};
+
+// How waits are written out in messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationStyle {
+    // "in 3 hours 12 minutes"
+    Compound,
+    // A Discord timestamp that counts down in the reader's own client
+    Relative
+}
+
+impl DurationStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DurationStyle::Compound => "compound",
+            DurationStyle::Relative => "relative"
+        }
+    }
+}
+
+impl std::str::FromStr for DurationStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "compound" => Ok(DurationStyle::Compound),
+            "relative" => Ok(DurationStyle::Relative),
+            _ => Err(format!("Unknown duration style \"{}\", use compound or relative", value))
+        }
+    }
+}
+
+// Languages durations can be written in, everything else falls back to English
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    German,
+    French,
+    Spanish
+}
+
+impl Locale {
+    // Takes a Discord locale like en-US or es-419
+    pub fn from_discord(locale: Option<&str>) -> Locale {
+        let language = locale.unwrap_or("en").split('-').next().unwrap_or("en");
+
+        match language {
+            "de" => Locale::German,
+            "fr" => Locale::French,
+            "es" => Locale::Spanish,
+            _ => Locale::English
+        }
+    }
+
+    // French uses the singular for zero as well
+    fn is_singular(&self, count: i64) -> bool {
+        match self {
+            Locale::French => count == 0 || count == 1,
+            _ => count == 1
+        }
+    }
+
+    fn unit_word(&self, unit: Unit, singular: bool) -> &'static str {
+        let (one, other) = match (self, unit) {
+            (Locale::English, Unit::Day) => ("day", "days"),
+            (Locale::English, Unit::Hour) => ("hour", "hours"),
+            (Locale::English, Unit::Minute) => ("minute", "minutes"),
+            (Locale::English, Unit::Second) => ("second", "seconds"),
+            (Locale::German, Unit::Day) => ("Tag", "Tagen"),
+            (Locale::German, Unit::Hour) => ("Stunde", "Stunden"),
+            (Locale::German, Unit::Minute) => ("Minute", "Minuten"),
+            (Locale::German, Unit::Second) => ("Sekunde", "Sekunden"),
+            (Locale::French, Unit::Day) => ("jour", "jours"),
+            (Locale::French, Unit::Hour) => ("heure", "heures"),
+            (Locale::French, Unit::Minute) => ("minute", "minutes"),
+            (Locale::French, Unit::Second) => ("seconde", "secondes"),
+            (Locale::Spanish, Unit::Day) => ("día", "días"),
+            (Locale::Spanish, Unit::Hour) => ("hora", "horas"),
+            (Locale::Spanish, Unit::Minute) => ("minuto", "minutos"),
+            (Locale::Spanish, Unit::Second) => ("segundo", "segundos")
+        };
+
+        if singular {one} else {other}
+    }
+
+    // Goes in front of a duration to say it's still to come
+    fn future_word(&self) -> &'static str {
+        match self {
+            Locale::English | Locale::German => "in",
+            Locale::French => "dans",
+            Locale::Spanish => "en"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Day,
+    Hour,
+    Minute,
+    Second
+}
+
+// Largest first, with their length in seconds
+const UNITS: [(Unit, i64); 4] = [(Unit::Day, 86400), (Unit::Hour, 3600), (Unit::Minute, 60), (Unit::Second, 1)];
+
+// The largest unit and the one after it, like "3 hours 12 minutes" or "1 day".
+// Part seconds round up, so a wait never reads as 0 seconds while it's still going.
+// Written to follow the locale's word for "in", which German follows with the dative.
+fn format_duration(duration: Duration, locale: Locale) -> String {
+    let mut remaining = std::cmp::max(0, (duration.num_milliseconds() + 999) / 1000);
+    let counts: Vec<(Unit, i64)> = UNITS.iter()
+        .map(|(unit, length)| {
+            let count = remaining / length;
+            remaining %= length;
+            (*unit, count)
+        })
+        .collect();
+
+    let Some(largest) = counts.iter().position(|(_, count)| *count > 0) else {
+        return format!("0 {}", locale.unit_word(Unit::Second, locale.is_singular(0)));
+    };
+
+    counts[largest..].iter()
+        .take(2)
+        .filter(|(_, count)| *count > 0)
+        .map(|(unit, count)| format!("{} {}", count, locale.unit_word(*unit, locale.is_singular(*count))))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// How long until a moment, written to follow words like "try again"
+pub fn format_until(until: DateTime<Utc>, now: DateTime<Utc>, style: DurationStyle, locale: Locale) -> String {
+    match style {
+        DurationStyle::Compound => format!("{} {}", locale.future_word(), format_duration(until - now, locale)),
+        // Discord writes these in the reader's language by itself. Rounded up like above.
+        DurationStyle::Relative => format!("<t:{}:R>", (until.timestamp_millis() + 999).div_euclid(1000))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn wait(seconds: i64, millis: i64) -> String {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        format_until(now + Duration::seconds(seconds) + Duration::milliseconds(millis), now, DurationStyle::Compound, Locale::English)
+    }
+
+    #[test]
+    fn compound() {
+        assert_eq!(wait(0, 0), "in 0 seconds");
+        assert_eq!(wait(1, 0), "in 1 second");
+        assert_eq!(wait(0, 1), "in 1 second");
+        assert_eq!(wait(59, 500), "in 1 minute");
+        assert_eq!(wait(3 * 3600 + 12 * 60 + 30, 0), "in 3 hours 12 minutes");
+        assert_eq!(wait(86400 + 30, 0), "in 1 day");
+        assert_eq!(wait(2 * 86400 + 3600, 0), "in 2 days 1 hour");
+        // Already past reads as no wait at all
+        assert_eq!(wait(-5, 0), "in 0 seconds");
+    }
+
+    #[test]
+    fn relative() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let until = now + Duration::milliseconds(1500);
+        assert_eq!(format_until(until, now, DurationStyle::Relative, Locale::German), format!("<t:{}:R>", now.timestamp() + 2));
+    }
+
+    #[test]
+    fn locales() {
+        assert_eq!(Locale::from_discord(Some("es-419")), Locale::Spanish);
+        assert_eq!(Locale::from_discord(Some("pt-BR")), Locale::English);
+        assert_eq!(Locale::from_discord(None), Locale::English);
+
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let until = now + Duration::hours(25);
+        assert_eq!(format_until(until, now, DurationStyle::Compound, Locale::German), "in 1 Tag 1 Stunde");
+        assert_eq!(format_until(until + Duration::hours(1), now, DurationStyle::Compound, Locale::Spanish), "en 1 día 2 horas");
+        // French keeps zero singular
+        assert_eq!(format_until(now, now, DurationStyle::Compound, Locale::French), "dans 0 seconde");
+    }
+
+    #[test]
+    fn styles() {
+        for style in [DurationStyle::Compound, DurationStyle::Relative] {
+            assert_eq!(style.as_str().parse::<DurationStyle>(), Ok(style));
+        }
+        assert!("fancy".parse::<DurationStyle>().is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum CoinSide {
+    #[name = "heads"]
+    Heads,
+    #[name = "tails"]
+    Tails
+}
+
+impl CoinSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoinSide::Heads => "heads",
+            CoinSide::Tails => "tails"
+        }
+    }
+}
+
+pub fn flip_coin(rng: &mut impl Rng) -> CoinSide {
+    if rng.gen_bool(0.5) {CoinSide::Heads} else {CoinSide::Tails}
+}
+
+// Dice bets are on a roll of 1 to 100 landing over or under the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum DiceBet {
+    #[name = "over"]
+    Over,
+    #[name = "under"]
+    Under
+}
+
+impl DiceBet {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiceBet::Over => "over",
+            DiceBet::Under => "under"
+        }
+    }
+
+    // Percent chance of winning, the target itself always loses
+    pub fn chance(&self, target: u32) -> u32 {
+        match self {
+            DiceBet::Over => 100u32.saturating_sub(target),
+            DiceBet::Under => target.saturating_sub(1)
+        }
+    }
+
+    pub fn wins(&self, target: u32, roll: u32) -> bool {
+        match self {
+            DiceBet::Over => roll > target,
+            DiceBet::Under => roll < target
+        }
+    }
+}
+
+pub fn roll_dice(rng: &mut impl Rng) -> u32 {
+    rng.gen_range(1..=100)
+}
+
+// Reel symbols with how often they come up, commonest first
+const REEL: [(&str, u32); 5] = [("🍒", 6), ("🍋", 5), ("🔔", 4), ("⭐", 2), ("💎", 1)];
+
+// Times the stake paid back for three of a kind. Together with two cherries paying
+// 2x, about 95% of stakes come back in the long run.
+const PAYTABLE: [(&str, u64); 5] = [("🍒", 4), ("🍋", 8), ("🔔", 12), ("⭐", 30), ("💎", 100)];
+const TWO_CHERRIES: u64 = 2;
+
+pub fn spin_reels(rng: &mut impl Rng) -> [&'static str; 3] {
+    let total: u32 = REEL.iter().map(|(_, weight)| weight).sum();
+
+    std::array::from_fn(|_| {
+        let mut roll = rng.gen_range(0..total);
+        REEL.iter()
+            .find(|(_, weight)| {
+                let landed = roll < *weight;
+                roll = roll.saturating_sub(*weight);
+                landed
+            })
+            .map(|(symbol, _)| *symbol)
+            .unwrap_or(REEL[0].0)
+    })
+}
+
+// Times the stake a spin pays back, zero when it lost
+pub fn slots_multiplier(reels: &[&str; 3]) -> u64 {
+    if reels[0] == reels[1] && reels[1] == reels[2] {
+        return PAYTABLE.iter()
+            .find(|(symbol, _)| *symbol == reels[0])
+            .map(|(_, multiplier)| *multiplier)
+            .unwrap_or(0);
+    }
+
+    match reels.iter().filter(|symbol| **symbol == "🍒").count() {
+        2 => TWO_CHERRIES,
+        _ => 0
+    }
+}
+
+// The paytable as shown to players, best first
+pub fn paytable() -> String {
+    let mut lines: Vec<String> = PAYTABLE.iter()
+        .rev()
+        .map(|(symbol, multiplier)| format!("{}{}{} pays {}x", symbol, symbol, symbol, multiplier))
+        .collect();
+    lines.push(format!("any two 🍒 pays {}x", TWO_CHERRIES));
+
+    lines.join("\n")
+}
+
+// Cards are dealt from an endless shoe, so every rank is always as likely
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    // 1 for aces up to 13 for kings
+    pub rank: u8,
+    pub suit: char
+}
+
+impl Card {
+    pub fn draw(rng: &mut impl Rng) -> Card {
+        Card {
+            rank: rng.gen_range(1..=13),
+            suit: ['♠', '♥', '♦', '♣'][rng.gen_range(0..4)]
+        }
+    }
+
+    fn value(&self) -> u32 {
+        std::cmp::min(self.rank, 10) as u32
+    }
+}
+
+impl std::fmt::Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rank = match self.rank {
+            1 => "A".to_string(),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            rank => rank.to_string()
+        };
+
+        write!(f, "`{}{}`", rank, self.suit)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hand(pub Vec<Card>);
+
+impl Hand {
+    // Aces count 11 unless that would bust the hand
+    pub fn value(&self) -> u32 {
+        let hard: u32 = self.0.iter().map(Card::value).sum();
+        let has_ace = self.0.iter().any(|card| card.rank == 1);
+
+        if has_ace && hard + 10 <= 21 {hard + 10} else {hard}
+    }
+
+    pub fn is_blackjack(&self) -> bool {
+        self.0.len() == 2 && self.value() == 21
+    }
+
+    pub fn is_bust(&self) -> bool {
+        self.value() > 21
+    }
+
+    pub fn cards(&self) -> String {
+        self.0.iter().map(Card::to_string).collect::<Vec<String>>().join(" ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlackjackOutcome {
+    Blackjack,
+    DealerBust,
+    Win,
+    Push,
+    Loss,
+    Bust,
+    DealerBlackjack
+}
+
+impl BlackjackOutcome {
+    // Paws won (positive) or lost (negative) for a stake, naturals pay 3:2
+    pub fn settle(&self, stake: u64) -> i64 {
+        let stake = stake as i64;
+        match self {
+            BlackjackOutcome::Blackjack => std::cmp::max(1, stake * 3 / 2),
+            BlackjackOutcome::DealerBust | BlackjackOutcome::Win => stake,
+            BlackjackOutcome::Push => 0,
+            BlackjackOutcome::Loss | BlackjackOutcome::Bust | BlackjackOutcome::DealerBlackjack => -stake
+        }
+    }
+}
+
+// A hand of blackjack against the dealer. No splits, doubles or insurance, and the
+// dealer stands on every 17.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blackjack {
+    pub player: Hand,
+    pub dealer: Hand
+}
+
+impl Blackjack {
+    pub fn deal(rng: &mut impl Rng) -> Blackjack {
+        Blackjack {
+            player: Hand(vec![Card::draw(rng), Card::draw(rng)]),
+            dealer: Hand(vec![Card::draw(rng), Card::draw(rng)])
+        }
+    }
+
+    // Naturals end the hand before anyone draws
+    pub fn natural(&self) -> Option<BlackjackOutcome> {
+        match (self.player.is_blackjack(), self.dealer.is_blackjack()) {
+            (true, true) => Some(BlackjackOutcome::Push),
+            (true, false) => Some(BlackjackOutcome::Blackjack),
+            (false, true) => Some(BlackjackOutcome::DealerBlackjack),
+            (false, false) => None
+        }
+    }
+
+    // Draws for the player, ending the hand if they bust
+    pub fn hit(&mut self, rng: &mut impl Rng) -> Option<BlackjackOutcome> {
+        self.player.0.push(Card::draw(rng));
+
+        Some(BlackjackOutcome::Bust).filter(|_| self.player.is_bust())
+    }
+
+    // The dealer plays out their hand against the player's
+    pub fn stand(&mut self, rng: &mut impl Rng) -> BlackjackOutcome {
+        while self.dealer.value() < 17 {
+            self.dealer.0.push(Card::draw(rng));
+        }
+
+        let (player, dealer) = (self.player.value(), self.dealer.value());
+        if self.dealer.is_bust() {
+            BlackjackOutcome::DealerBust
+        } else if player > dealer {
+            BlackjackOutcome::Win
+        } else if player == dealer {
+            BlackjackOutcome::Push
+        } else {
+            BlackjackOutcome::Loss
+        }
+    }
+}
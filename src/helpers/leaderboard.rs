@@ -0,0 +1,197 @@
+use super::store::PawStore;
+use super::structs::RankingMode;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+pub const PAGE_SIZE: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RankedFarmer {
+    pub rank: u64,
+    pub user_id: u64,
+    pub count: u64
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaderboardPage {
+    // 1 indexed, may be past the last page
+    pub page: u64,
+    pub page_count: u64,
+    pub farmers: u64,
+    pub total_paws: u64,
+    pub entries: Vec<RankedFarmer>
+}
+
+impl LeaderboardPage {
+    // Farmers on the pages after this one
+    pub fn farmers_after(&self) -> u64 {
+        self.farmers.saturating_sub(page_offset(self.page) + self.entries.len() as u64)
+    }
+}
+
+// Where a single farmer sits on their server's leaderboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standing {
+    pub rank: u64,
+    // 1 indexed place in the list once ties are broken by user id
+    pub position: u64,
+    pub count: u64
+}
+
+impl Standing {
+    pub fn page(&self) -> u64 {
+        page_of_position(self.position)
+    }
+}
+
+// Always at least one page, even when nobody has paws yet
+pub fn page_count(farmers: u64) -> u64 {
+    std::cmp::max(1, farmers.div_ceil(PAGE_SIZE))
+}
+
+pub fn page_offset(page: u64) -> u64 {
+    page.saturating_sub(1) * PAGE_SIZE
+}
+
+pub fn page_of_position(position: u64) -> u64 {
+    position.saturating_sub(1) / PAGE_SIZE + 1
+}
+
+// Rank of a farmer with count paws: one more than the farmers (or distinct counts) above them
+async fn rank_of(store: &dyn PawStore, server_id: u64, count: u64, mode: RankingMode) -> Result<u64, Error> {
+    let above = store.count_farmers_above(server_id, count, mode == RankingMode::Dense).await?;
+    Ok(above + 1)
+}
+
+pub async fn get_page(store: &dyn PawStore, server_id: u64, page: u64, mode: RankingMode) -> Result<LeaderboardPage, Error> {
+    let page = std::cmp::max(1, page);
+    let offset = page_offset(page);
+    let (farmers, total_paws) = store.get_leaderboard_totals(server_id).await?;
+    let leaderboard = store.get_leaderboard(server_id, offset, PAGE_SIZE).await?;
+
+    let mut entries: Vec<RankedFarmer> = Vec::with_capacity(leaderboard.len());
+    for (index, farmer) in leaderboard.iter().enumerate() {
+        let count = farmer.count as u64;
+
+        let rank = match entries.last() {
+            // Ties share a rank
+            Some(previous) if previous.count == count => previous.rank,
+            Some(previous) => match mode {
+                RankingMode::Standard => offset + index as u64 + 1,
+                RankingMode::Dense => previous.rank + 1
+            },
+            // The first farmer on the page may be tied with someone on the page before
+            None => rank_of(store, server_id, count, mode).await?
+        };
+
+        entries.push(RankedFarmer { rank, user_id: farmer.user_id as u64, count });
+    }
+
+    Ok(LeaderboardPage {
+        page,
+        page_count: page_count(farmers),
+        farmers,
+        total_paws,
+        entries
+    })
+}
+
+// None if the user has never held paws on the server
+pub async fn get_standing(store: &dyn PawStore, server_id: u64, user_id: u64, mode: RankingMode) -> Result<Option<Standing>, Error> {
+    // Farmers without a row aren't on the leaderboard at all
+    let (farmers, _) = store.get_leaderboard_totals(server_id).await?;
+    if farmers == 0 {
+        return Ok(None);
+    }
+
+    let count = store.get_paw_count(user_id, server_id).await?;
+    let above = store.count_farmers_above(server_id, count, false).await?;
+    let tied_before = store.count_tied_before(server_id, count, user_id).await?;
+    let position = above + tied_before + 1;
+
+    // A zero count is also what a missing row looks like, check they are really listed
+    if count == 0 {
+        let listed = store.get_leaderboard(server_id, position - 1, 1).await?;
+        if !listed.iter().any(|farmer| farmer.user_id as u64 == user_id) {
+            return Ok(None);
+        }
+    }
+
+    let rank = match mode {
+        RankingMode::Standard => above + 1,
+        RankingMode::Dense => rank_of(store, server_id, count, mode).await?
+    };
+
+    Ok(Some(Standing { rank, position, count }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::store::memory::MemoryStore;
+    use crate::helpers::structs::{LedgerReason, Transfer};
+
+    // Twelve farmers, where the 10th and 11th are tied across the page break
+    async fn server(store: &MemoryStore) {
+        let counts = [100, 90, 90, 80, 70, 60, 50, 40, 30, 20, 20, 10];
+        for (user_id, count) in counts.iter().enumerate() {
+            let transfer = Transfer {
+                server_id: 1,
+                actor_id: 0,
+                source_id: None,
+                target_id: Some(user_id as u64 + 1),
+                amount: *count,
+                reason: LedgerReason::GambleWin
+            };
+            store.transfer_paws(&transfer).await.unwrap();
+        }
+    }
+
+    #[test]
+    fn pages() {
+        assert_eq!(page_count(0), 1);
+        assert_eq!(page_count(10), 1);
+        assert_eq!(page_count(11), 2);
+        assert_eq!(page_offset(0), 0);
+        assert_eq!(page_offset(1), 0);
+        assert_eq!(page_offset(3), 20);
+        assert_eq!(page_of_position(1), 1);
+        assert_eq!(page_of_position(10), 1);
+        assert_eq!(page_of_position(11), 2);
+    }
+
+    #[tokio::test]
+    async fn ties_share_a_rank() {
+        let store = MemoryStore::default();
+        server(&store).await;
+
+        let first = get_page(&store, 1, 1, RankingMode::Standard).await.unwrap();
+        let ranks: Vec<u64> = first.entries.iter().map(|farmer| farmer.rank).collect();
+        assert_eq!(ranks, [1, 2, 2, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!((first.page_count, first.farmers, first.farmers_after()), (2, 12, 2));
+
+        // The tie carries over to the next page
+        let second = get_page(&store, 1, 2, RankingMode::Standard).await.unwrap();
+        let ranks: Vec<u64> = second.entries.iter().map(|farmer| farmer.rank).collect();
+        assert_eq!(ranks, [10, 12]);
+        assert_eq!(second.farmers_after(), 0);
+
+        let dense = get_page(&store, 1, 2, RankingMode::Dense).await.unwrap();
+        let ranks: Vec<u64> = dense.entries.iter().map(|farmer| farmer.rank).collect();
+        assert_eq!(ranks, [9, 10]);
+    }
+
+    #[tokio::test]
+    async fn standings() {
+        let store = MemoryStore::default();
+        server(&store).await;
+
+        let standing = get_standing(&store, 1, 11, RankingMode::Standard).await.unwrap().unwrap();
+        assert_eq!(standing, Standing { rank: 10, position: 11, count: 20 });
+        assert_eq!(standing.page(), 2);
+
+        let dense = get_standing(&store, 1, 11, RankingMode::Dense).await.unwrap().unwrap();
+        assert_eq!(dense.rank, 9);
+
+        assert_eq!(get_standing(&store, 1, 99, RankingMode::Standard).await.unwrap(), None);
+    }
+}
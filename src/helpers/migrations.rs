@@ -0,0 +1,218 @@
+use sqlx::{Executor, PgPool};
+use tracing::log::info;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// Arbitrary key for the advisory lock that stops two bots migrating at once
+const MIGRATION_LOCK_KEY: i64 = 0x0070_6177_5f62_6f74;
+
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str
+}
+
+// Every schema change, in the order it has to be applied. Never edit one that has
+// shipped, add a new file instead. Each backend keeps its own numbering.
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "initial", sql: include_str!("../../migrations/postgres/0001_initial.sql") },
+    Migration { version: 2, name: "paw_ledger", sql: include_str!("../../migrations/postgres/0002_paw_ledger.sql") },
+    Migration { version: 3, name: "outcome_chances", sql: include_str!("../../migrations/postgres/0003_outcome_chances.sql") },
+    Migration { version: 4, name: "ranking_mode", sql: include_str!("../../migrations/postgres/0004_ranking_mode.sql") },
+    Migration { version: 5, name: "user_profiles", sql: include_str!("../../migrations/postgres/0005_user_profiles.sql") },
+    Migration { version: 6, name: "shop", sql: include_str!("../../migrations/postgres/0006_shop.sql") },
+    Migration { version: 7, name: "role_shop", sql: include_str!("../../migrations/postgres/0007_role_shop.sql") },
+    Migration { version: 8, name: "daily_streaks", sql: include_str!("../../migrations/postgres/0008_daily_streaks.sql") },
+    Migration { version: 9, name: "daily_reset", sql: include_str!("../../migrations/postgres/0009_daily_reset.sql") },
+    Migration { version: 10, name: "duration_style", sql: include_str!("../../migrations/postgres/0010_duration_style.sql") },
+    Migration { version: 11, name: "reminders", sql: include_str!("../../migrations/postgres/0011_reminders.sql") },
+    Migration { version: 12, name: "stake_limits", sql: include_str!("../../migrations/postgres/0012_stake_limits.sql") },
+    Migration { version: 13, name: "game_results", sql: include_str!("../../migrations/postgres/0013_game_results.sql") },
+    Migration { version: 14, name: "duels", sql: include_str!("../../migrations/postgres/0014_duels.sql") },
+    Migration { version: 15, name: "ledger_history", sql: include_str!("../../migrations/postgres/0015_ledger_history.sql") },
+    Migration { version: 16, name: "admin_tools", sql: include_str!("../../migrations/postgres/0016_admin_tools.sql") },
+    Migration { version: 17, name: "economy_import", sql: include_str!("../../migrations/postgres/0017_economy_import.sql") },
+    Migration { version: 18, name: "open_games", sql: include_str!("../../migrations/postgres/0018_open_games.sql") },
+    Migration { version: 19, name: "interval_bounds", sql: include_str!("../../migrations/postgres/0019_interval_bounds.sql") },
+];
+
+#[cfg(feature = "sqlite")]
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "initial", sql: include_str!("../../migrations/sqlite/0001_initial.sql") },
+    Migration { version: 2, name: "ranking_mode", sql: include_str!("../../migrations/sqlite/0002_ranking_mode.sql") },
+    Migration { version: 3, name: "user_profiles", sql: include_str!("../../migrations/sqlite/0003_user_profiles.sql") },
+    Migration { version: 4, name: "shop", sql: include_str!("../../migrations/sqlite/0004_shop.sql") },
+    Migration { version: 5, name: "role_shop", sql: include_str!("../../migrations/sqlite/0005_role_shop.sql") },
+    Migration { version: 6, name: "daily_streaks", sql: include_str!("../../migrations/sqlite/0006_daily_streaks.sql") },
+    Migration { version: 7, name: "daily_reset", sql: include_str!("../../migrations/sqlite/0007_daily_reset.sql") },
+    Migration { version: 8, name: "duration_style", sql: include_str!("../../migrations/sqlite/0008_duration_style.sql") },
+    Migration { version: 9, name: "reminders", sql: include_str!("../../migrations/sqlite/0009_reminders.sql") },
+    Migration { version: 10, name: "stake_limits", sql: include_str!("../../migrations/sqlite/0010_stake_limits.sql") },
+    Migration { version: 11, name: "game_results", sql: include_str!("../../migrations/sqlite/0011_game_results.sql") },
+    Migration { version: 12, name: "duels", sql: include_str!("../../migrations/sqlite/0012_duels.sql") },
+    Migration { version: 13, name: "ledger_history", sql: include_str!("../../migrations/sqlite/0013_ledger_history.sql") },
+    Migration { version: 14, name: "admin_tools", sql: include_str!("../../migrations/sqlite/0014_admin_tools.sql") },
+    Migration { version: 15, name: "economy_import", sql: include_str!("../../migrations/sqlite/0015_economy_import.sql") },
+    Migration { version: 16, name: "open_games", sql: include_str!("../../migrations/sqlite/0016_open_games.sql") },
+    Migration { version: 17, name: "interval_bounds", sql: include_str!("../../migrations/sqlite/0017_interval_bounds.sql") },
+];
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub current_version: i64,
+    pub latest_version: i64,
+    pub pending: Vec<&'static Migration>
+}
+
+impl MigrationStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+async fn create_migrations_table(pool: &PgPool) -> Result<(), Error> {
+    sqlx::raw_sql(r#"
+        CREATE SCHEMA IF NOT EXISTS "paw-bot";
+        CREATE TABLE IF NOT EXISTS "paw-bot".schema_migrations (
+            version int8 NOT NULL,
+            name text NOT NULL,
+            applied_at timestamptz NOT NULL DEFAULT now(),
+            CONSTRAINT schema_migrations_pkey PRIMARY KEY (version)
+        );
+    "#)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Compares what Postgres has applied against the migrations in this binary
+pub async fn migration_status(pool: &PgPool) -> Result<MigrationStatus, Error> {
+    create_migrations_table(pool).await?;
+
+    let applied = sqlx::query_as::<_,(i64, String)>("SELECT version, name FROM \"paw-bot\".\"schema_migrations\" ORDER BY version")
+        .fetch_all(pool)
+        .await?;
+
+    check_migrations(POSTGRES_MIGRATIONS, &applied)
+}
+
+// Works out what is left to apply given the (version, name) pairs a database has
+// recorded, refusing databases that have been migrated further than this build
+pub fn check_migrations(migrations: &'static [Migration], applied: &[(i64, String)]) -> Result<MigrationStatus, Error> {
+    let latest_version = migrations.last().map(|migration| migration.version).unwrap_or(0);
+
+    for (version, name) in applied {
+        match migrations.iter().find(|migration| migration.version == *version) {
+            Some(migration) if migration.name == name => {}
+            Some(migration) => {
+                return Err(format!("Migration {} is recorded as \"{}\" but this build calls it \"{}\"", version, name, migration.name).into());
+            }
+            // Running an old build against a newer schema could corrupt data
+            None => {
+                return Err(format!("Database has migration {} ({}) which this build doesn't know about, it is newer than the bot", version, name).into());
+            }
+        }
+    }
+
+    let pending = migrations.iter()
+        .filter(|migration| !applied.iter().any(|(version, _)| *version == migration.version))
+        .collect();
+
+    Ok(MigrationStatus {
+        current_version: applied.last().map(|(version, _)| *version).unwrap_or(0),
+        latest_version,
+        pending
+    })
+}
+
+// Applies every pending migration, each in its own transaction. Returns how many ran.
+pub async fn run_migrations(pool: &PgPool) -> Result<usize, Error> {
+    let status = migration_status(pool).await?;
+    let mut applied = 0;
+
+    for migration in status.pending {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *transaction)
+            .await?;
+
+        // Another instance may have applied it while we waited for the lock
+        let already_applied = sqlx::query_scalar::<_,bool>("SELECT EXISTS (SELECT 1 FROM \"paw-bot\".\"schema_migrations\" WHERE version = $1)")
+            .bind(migration.version)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        if already_applied {
+            continue;
+        }
+
+        info!("Applying migration {} ({})", migration.version, migration.name);
+
+        // Passing the SQL without arguments runs every statement in the file
+        transaction.execute(migration.sql).await?;
+
+        sqlx::query("INSERT INTO \"paw-bot\".\"schema_migrations\" (version, name) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(versions: std::ops::RangeInclusive<usize>) -> Vec<(i64, String)> {
+        POSTGRES_MIGRATIONS[versions.start() - 1..*versions.end()].iter()
+            .map(|migration| (migration.version, migration.name.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn versions_count_up() {
+        for (index, migration) in POSTGRES_MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1);
+        }
+        #[cfg(feature = "sqlite")]
+        for (index, migration) in SQLITE_MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1);
+        }
+    }
+
+    #[test]
+    fn pending_migrations() {
+        let latest = POSTGRES_MIGRATIONS.len() as i64;
+
+        let fresh = check_migrations(POSTGRES_MIGRATIONS, &[]).unwrap();
+        assert_eq!((fresh.current_version, fresh.latest_version, fresh.pending.len() as i64), (0, latest, latest));
+
+        let behind = check_migrations(POSTGRES_MIGRATIONS, &applied(1..=1)).unwrap();
+        assert_eq!(behind.current_version, 1);
+        assert_eq!(behind.pending[0].version, 2);
+        assert!(!behind.is_up_to_date());
+
+        let current = check_migrations(POSTGRES_MIGRATIONS, &applied(1..=POSTGRES_MIGRATIONS.len())).unwrap();
+        assert_eq!(current.current_version, latest);
+        assert!(current.is_up_to_date());
+    }
+
+    #[test]
+    fn refuses_unknown_migrations() {
+        let mut renamed = applied(1..=2);
+        renamed[1].1 = "something_else".to_string();
+        assert!(check_migrations(POSTGRES_MIGRATIONS, &renamed).is_err());
+
+        let mut newer = applied(1..=POSTGRES_MIGRATIONS.len());
+        newer.push((POSTGRES_MIGRATIONS.len() as i64 + 1, "from_the_future".to_string()));
+        assert!(check_migrations(POSTGRES_MIGRATIONS, &newer).is_err());
+    }
+}
@@ -1,12 +1,31 @@
+use std::sync::Arc;
 use config::Config;
-use sqlx;
+use profiles::ProfileCache;
+use settings::SettingsCache;
+use store::PawStore;
+pub mod backup;
 pub mod config;
-pub mod database;
+pub mod daily;
+pub mod duels;
+pub mod durations;
+pub mod games;
+pub mod leaderboard;
+pub mod migrations;
+pub mod open_games;
+pub mod outcome;
+pub mod profiles;
+pub mod reminders;
+pub mod rentals;
+pub mod settings;
+pub mod stakes;
+pub mod store;
 pub mod structs;
 pub mod types;
 
 #[derive(Debug)]
 pub struct AppState {
     pub env: Config,
-    pub db: sqlx::PgPool
-}
\ No newline at end of file
+    pub store: Arc<dyn PawStore>,
+    pub settings: SettingsCache,
+    pub profiles: ProfileCache
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use chrono::Utc;
+use tracing::log::{info, warn};
+use super::store::PawStore;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// How long a game can stay open before its stake is given back. Even a blackjack
+// hand that hits until it busts is over well within this.
+pub const GAME_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+// How often games are checked, the game commands refund their own when they fail
+// so this only catches ones they couldn't
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Games are played through the bot that dealt them, so every one still open from
+// before a restart is refunded
+pub async fn refund_open_games(store: &dyn PawStore) -> Result<usize, Error> {
+    refund_games_before(store, Utc::now()).await
+}
+
+// Refunds games that have been open too long, for as long as the bot is running
+pub async fn expire_games(store: Arc<dyn PawStore>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = refund_games_before(store.as_ref(), Utc::now() - GAME_TIMEOUT).await {
+            warn!("Couldn't check for abandoned games: {:?}", error);
+        }
+    }
+}
+
+async fn refund_games_before(store: &dyn PawStore, cutoff: chrono::DateTime<Utc>) -> Result<usize, Error> {
+    let mut refunded = 0;
+
+    for game in store.get_open_games(cutoff).await? {
+        // Already settled or refunded if there's nothing to close
+        if store.refund_game(&game).await?.is_some() {
+            info!("Refunded {} paws to {} for an unfinished game in {}", game.stake, game.user_id, game.server_id);
+            refunded += 1;
+        }
+    }
+
+    Ok(refunded)
+}
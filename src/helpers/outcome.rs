@@ -0,0 +1,192 @@
+use rand::{Rng, RngCore};
+use super::structs::ServerSettings;
+
+// Everything a risky action (gamble, steal) can end in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    CriticalSuccess,
+    Success,
+    PartialSuccess,
+    Failure,
+    Caught
+}
+
+impl Outcome {
+    // Paws won (positive) or lost (negative) by the actor for a given stake, where
+    // winnings is what a plain success pays
+    pub fn settle(&self, stake: u64, winnings: u64) -> i64 {
+        let stake = stake as i64;
+        let winnings = winnings as i64;
+        match self {
+            Outcome::CriticalSuccess => winnings * 2,
+            Outcome::Success => winnings,
+            Outcome::PartialSuccess => std::cmp::max(1, winnings / 2),
+            Outcome::Failure => -stake,
+            // The fine is the stake on top of losing the stake
+            Outcome::Caught => -stake * 2
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Outcome::CriticalSuccess | Outcome::Success | Outcome::PartialSuccess)
+    }
+}
+
+// Decides how a risky action turns out
+pub trait OutcomeResolver {
+    fn resolve(&self, rng: &mut dyn RngCore) -> Outcome;
+}
+
+// Picks an outcome with a chance proportional to its weight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedOutcomes {
+    weights: Vec<(Outcome, u32)>
+}
+
+impl WeightedOutcomes {
+    pub fn new(weights: Vec<(Outcome, u32)>) -> WeightedOutcomes {
+        WeightedOutcomes { weights }
+    }
+
+    // Builds a table out of percentages, whatever is left over is a failure
+    pub fn from_percentages(critical: i32, success: i32, partial: i32, caught: i32) -> Result<WeightedOutcomes, String> {
+        let chances = [critical, success, partial, caught];
+        if chances.iter().any(|chance| !(0..=100).contains(chance)) {
+            return Err("Chances have to be between 0 and 100".to_string());
+        }
+
+        let total: i32 = chances.iter().sum();
+        if total > 100 {
+            return Err(format!("Chances add up to {}%, they can be at most 100%", total));
+        }
+
+        Ok(WeightedOutcomes::new(vec![
+            (Outcome::CriticalSuccess, critical as u32),
+            (Outcome::Success, success as u32),
+            (Outcome::PartialSuccess, partial as u32),
+            (Outcome::Caught, caught as u32),
+            (Outcome::Failure, (100 - total) as u32)
+        ]))
+    }
+
+    pub fn for_gamble(settings: &ServerSettings) -> Result<WeightedOutcomes, String> {
+        WeightedOutcomes::from_percentages(
+            settings.gamble_critical_chance,
+            settings.gamble_chance,
+            settings.gamble_partial_chance,
+            0 // Nobody catches you gambling
+        )
+    }
+
+    pub fn for_steal(settings: &ServerSettings) -> Result<WeightedOutcomes, String> {
+        WeightedOutcomes::from_percentages(
+            settings.steal_critical_chance,
+            settings.steal_chance,
+            settings.steal_partial_chance,
+            settings.steal_caught_chance
+        )
+    }
+
+    // Moves up to points of weight from failure to another outcome, for items that improve the odds
+    pub fn boost(mut self, outcome: Outcome, points: u32) -> WeightedOutcomes {
+        let failure = self.weights.iter().position(|(weighted, _)| *weighted == Outcome::Failure);
+        let Some(failure) = failure else {
+            return self;
+        };
+
+        let moved = std::cmp::min(points, self.weights[failure].1);
+        self.weights[failure].1 -= moved;
+
+        match self.weights.iter_mut().find(|(weighted, _)| *weighted == outcome) {
+            Some((_, weight)) => *weight += moved,
+            None => self.weights.push((outcome, moved))
+        }
+
+        self
+    }
+}
+
+impl OutcomeResolver for WeightedOutcomes {
+    fn resolve(&self, rng: &mut dyn RngCore) -> Outcome {
+        let total: u32 = self.weights.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return Outcome::Failure;
+        }
+
+        let mut roll = rng.gen_range(0..total);
+        for (outcome, weight) in &self.weights {
+            if roll < *weight {
+                return *outcome;
+            }
+            roll -= weight;
+        }
+
+        Outcome::Failure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn rolls(outcomes: &WeightedOutcomes) -> Vec<Outcome> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..1000).map(|_| outcomes.resolve(&mut rng)).collect()
+    }
+
+    #[test]
+    fn settles_stakes() {
+        assert_eq!(Outcome::CriticalSuccess.settle(10, 10), 20);
+        assert_eq!(Outcome::Success.settle(10, 10), 10);
+        assert_eq!(Outcome::PartialSuccess.settle(10, 10), 5);
+        assert_eq!(Outcome::PartialSuccess.settle(1, 1), 1);
+        assert_eq!(Outcome::Failure.settle(10, 10), -10);
+        assert_eq!(Outcome::Caught.settle(10, 10), -20);
+    }
+
+    #[test]
+    fn percentages() {
+        assert!(WeightedOutcomes::from_percentages(-1, 50, 0, 0).is_err());
+        assert!(WeightedOutcomes::from_percentages(0, 101, 0, 0).is_err());
+        assert!(WeightedOutcomes::from_percentages(30, 30, 30, 30).is_err());
+
+        // Whatever isn't assigned is a failure
+        let outcomes = WeightedOutcomes::from_percentages(0, 0, 0, 0).unwrap();
+        assert!(rolls(&outcomes).iter().all(|outcome| *outcome == Outcome::Failure));
+    }
+
+    #[test]
+    fn rolls_only_weighted_outcomes() {
+        let outcomes = WeightedOutcomes::from_percentages(0, 100, 0, 0).unwrap();
+        assert!(rolls(&outcomes).iter().all(|outcome| *outcome == Outcome::Success));
+
+        let outcomes = WeightedOutcomes::from_percentages(10, 40, 0, 20).unwrap();
+        let rolled = rolls(&outcomes);
+        assert!(!rolled.contains(&Outcome::PartialSuccess));
+        for outcome in [Outcome::CriticalSuccess, Outcome::Success, Outcome::Caught, Outcome::Failure] {
+            assert!(rolled.contains(&outcome));
+        }
+
+        // Nothing to roll is a failure
+        assert!(rolls(&WeightedOutcomes::new(vec![])).iter().all(|outcome| *outcome == Outcome::Failure));
+    }
+
+    #[test]
+    fn boosts_move_weight_from_failure() {
+        let outcomes = WeightedOutcomes::from_percentages(0, 50, 0, 0).unwrap().boost(Outcome::Success, 20);
+        assert_eq!(outcomes, WeightedOutcomes::from_percentages(0, 70, 0, 0).unwrap());
+
+        // Never more than failure had
+        let outcomes = WeightedOutcomes::from_percentages(0, 90, 0, 0).unwrap().boost(Outcome::Success, 20);
+        assert!(rolls(&outcomes).iter().all(|outcome| *outcome == Outcome::Success));
+    }
+
+    #[test]
+    fn gambles_are_never_caught() {
+        let settings = ServerSettings { gamble_chance: 0, steal_chance: 0, steal_caught_chance: 100, ..ServerSettings::default() };
+        assert!(rolls(&WeightedOutcomes::for_gamble(&settings).unwrap()).iter().all(|outcome| *outcome == Outcome::Failure));
+        assert!(rolls(&WeightedOutcomes::for_steal(&settings).unwrap()).iter().all(|outcome| *outcome == Outcome::Caught));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use chrono::{Duration, Utc};
+use poise::serenity_prelude as serenity;
+use serenity::{futures::future::join_all, CacheHttp, User, UserId};
+use tracing::log::warn;
+use super::store::PawStore;
+use super::structs::UserProfile;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// Discord names and avatars, kept so the leaderboard doesn't have to ask Discord
+// for every farmer on every page. Lookups go memory, then the database, then
+// Discord, and each step only asks for the users the one before it missed.
+#[derive(Debug)]
+pub struct ProfileCache {
+    ttl: Duration,
+    persist: bool,
+    profiles: RwLock<HashMap<u64, UserProfile>>
+}
+
+// What a user is called now, their server nickname aside
+pub fn display_name(user: &User) -> &str {
+    user.global_name.as_deref().unwrap_or(&user.name)
+}
+
+fn profile_from_user(user: &User) -> UserProfile {
+    UserProfile {
+        user_id: user.id.get() as i64,
+        display_name: display_name(user).to_string(),
+        avatar_url: user.avatar_url(),
+        updated_at: Utc::now()
+    }
+}
+
+impl ProfileCache {
+    pub fn new(ttl: Duration, persist: bool) -> ProfileCache {
+        ProfileCache {
+            ttl,
+            persist,
+            profiles: RwLock::new(HashMap::new())
+        }
+    }
+
+    fn is_fresh(&self, profile: &UserProfile) -> bool {
+        Utc::now() - profile.updated_at < self.ttl
+    }
+
+    fn cached(&self, user_id: u64) -> Option<UserProfile> {
+        self.profiles.read().unwrap().get(&user_id).cloned()
+    }
+
+    async fn save(&self, store: &dyn PawStore, profiles: Vec<UserProfile>) -> Result<(), Error> {
+        if profiles.is_empty() {
+            return Ok(());
+        }
+
+        if self.persist {
+            store.upsert_user_profiles(&profiles).await?;
+        }
+
+        let mut cached = self.profiles.write().unwrap();
+        for profile in profiles {
+            cached.insert(profile.user_id as u64, profile);
+        }
+
+        Ok(())
+    }
+
+    // Called whenever someone runs a command, so active farmers never need a lookup.
+    // Only writes when the profile is stale or has changed.
+    pub async fn remember(&self, store: &dyn PawStore, user: &User) -> Result<(), Error> {
+        let profile = profile_from_user(user);
+
+        let unchanged = self.cached(user.id.get()).is_some_and(|cached| {
+            self.is_fresh(&cached) && cached.display_name == profile.display_name && cached.avatar_url == profile.avatar_url
+        });
+        if unchanged {
+            return Ok(());
+        }
+
+        self.save(store, vec![profile]).await
+    }
+
+    // Profiles for every user asked for. Users Discord can't find are left out.
+    pub async fn get_many(&self, cache_http: impl CacheHttp, store: &dyn PawStore, user_ids: &[u64]) -> Result<HashMap<u64, UserProfile>, Error> {
+        let mut found: HashMap<u64, UserProfile> = HashMap::new();
+        // Out of date, but better than nothing if Discord doesn't answer
+        let mut stale: HashMap<u64, UserProfile> = HashMap::new();
+
+        for user_id in user_ids {
+            match self.cached(*user_id) {
+                Some(profile) if self.is_fresh(&profile) => { found.insert(*user_id, profile); }
+                Some(profile) => { stale.insert(*user_id, profile); }
+                None => {}
+            }
+        }
+
+        // Anyone else might have been saved by an earlier run of the bot
+        let missing: Vec<u64> = user_ids.iter().copied().filter(|user_id| !found.contains_key(user_id)).collect();
+        if self.persist && !missing.is_empty() {
+            let mut fresh = Vec::new();
+            for profile in store.get_user_profiles(&missing).await? {
+                let user_id = profile.user_id as u64;
+                if self.is_fresh(&profile) {
+                    found.insert(user_id, profile.clone());
+                    fresh.push(profile);
+                } else {
+                    stale.insert(user_id, profile);
+                }
+            }
+
+            let mut cached = self.profiles.write().unwrap();
+            for profile in fresh {
+                cached.insert(profile.user_id as u64, profile);
+            }
+        }
+
+        let mut looked_up = Vec::new();
+        let mut missing: Vec<u64> = user_ids.iter().copied().filter(|user_id| !found.contains_key(user_id)).collect();
+
+        // Users serenity has seen on the gateway don't need a request
+        if let Some(cache) = cache_http.cache() {
+            missing.retain(|user_id| match cache.user(UserId::new(*user_id)) {
+                Some(user) => {
+                    looked_up.push(profile_from_user(&user));
+                    false
+                }
+                None => true
+            });
+        }
+
+        // Whatever is left goes to Discord all at once rather than one after another
+        let requests = missing.iter().map(|user_id| cache_http.http().get_user(UserId::new(*user_id)));
+        for (user_id, result) in missing.iter().zip(join_all(requests).await) {
+            match result {
+                Ok(user) => looked_up.push(profile_from_user(&user)),
+                Err(error) => {
+                    warn!("Couldn't look up user {}: {}", user_id, error);
+                    if let Some(profile) = stale.remove(user_id) {
+                        found.insert(*user_id, profile);
+                    }
+                }
+            }
+        }
+
+        for profile in &looked_up {
+            found.insert(profile.user_id as u64, profile.clone());
+        }
+        self.save(store, looked_up).await?;
+
+        Ok(found)
+    }
+}
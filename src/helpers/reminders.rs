@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use poise::serenity_prelude as serenity;
+use serenity::{ChannelId, CreateAllowedMentions, CreateMessage, GuildId, Http, Mentionable, StatusCode, UserId};
+use tracing::log::{info, warn};
+use super::store::PawStore;
+use super::structs::{Reminder, ReminderAction, ReminderDelivery};
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// How often reminders are checked, so they go out at most this late
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// Sends reminders as they come due, for as long as the bot is running. Reminders
+// that came due while the bot was down go out on the first check.
+pub async fn send_reminders(http: Arc<Http>, store: Arc<dyn PawStore>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = send_due_reminders(&http, store.as_ref()).await {
+            warn!("Couldn't check for due reminders: {:?}", error);
+        }
+    }
+}
+
+async fn send_due_reminders(http: &Http, store: &dyn PawStore) -> Result<(), Error> {
+    for reminder in store.get_due_reminders(Utc::now()).await? {
+        match deliver(http, &reminder).await {
+            Ok(()) => {}
+            // DMs are closed, or the channel is gone or off limits, so it can never be delivered
+            Err(serenity::Error::Http(error)) if matches!(error.status_code(), Some(StatusCode::FORBIDDEN | StatusCode::NOT_FOUND)) => {
+                info!("Dropped {} reminder for {} in {}: {}", reminder.action.as_str(), reminder.user_id, reminder.server_id, error);
+            }
+            // Kept around to try again on the next check
+            Err(error) => {
+                warn!("Couldn't send {} reminder to {} in {}: {}", reminder.action.as_str(), reminder.user_id, reminder.server_id, error);
+                continue;
+            }
+        }
+
+        store.clear_reminder(&reminder).await?;
+    }
+
+    Ok(())
+}
+
+fn reminder_text(action: ReminderAction) -> &'static str {
+    match action {
+        ReminderAction::Daily => "your daily paw is ready, keep that streak going!",
+        ReminderAction::Gamble => "you can gamble again",
+        ReminderAction::Steal => "you can steal again"
+    }
+}
+
+async fn deliver(http: &Http, reminder: &Reminder) -> Result<(), serenity::Error> {
+    let user_id = UserId::new(reminder.user_id);
+    let text = reminder_text(reminder.action);
+
+    match (reminder.delivery, reminder.channel_id) {
+        (ReminderDelivery::Channel, Some(channel_id)) => {
+            let message = CreateMessage::new()
+                .content(format!("⏰ {} {}", user_id.mention(), text))
+                .allowed_mentions(CreateAllowedMentions::new().users([user_id]));
+
+            ChannelId::new(channel_id).send_message(http, message).await?;
+        }
+        _ => {
+            // DMs come from every server the bot is on, so say which one
+            let guild = GuildId::new(reminder.server_id).to_partial_guild(http).await?;
+            let channel = user_id.create_dm_channel(http).await?;
+
+            channel.send_message(http, CreateMessage::new().content(format!("⏰ On **{}**, {}", guild.name, text))).await?;
+        }
+    }
+
+    Ok(())
+}
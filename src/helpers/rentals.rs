@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use poise::serenity_prelude as serenity;
+use serenity::{GuildId, Http, RoleId, StatusCode, UserId};
+use tracing::log::{info, warn};
+use super::store::PawStore;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// How often rentals are checked, so roles last at most this much longer than paid for
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Takes rented roles away once they run out, for as long as the bot is running.
+// Rentals that ran out while the bot was down are handled on the first check.
+pub async fn expire_role_rentals(http: Arc<Http>, store: Arc<dyn PawStore>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = remove_expired_roles(&http, store.as_ref()).await {
+            warn!("Couldn't check for expired role rentals: {:?}", error);
+        }
+    }
+}
+
+async fn remove_expired_roles(http: &Http, store: &dyn PawStore) -> Result<(), Error> {
+    for rental in store.get_expired_role_rentals(Utc::now()).await? {
+        let removed = http.remove_member_role(
+            GuildId::new(rental.server_id),
+            UserId::new(rental.user_id),
+            RoleId::new(rental.role_id),
+            Some("Paw role rental ran out")
+        ).await;
+
+        match removed {
+            Ok(()) => info!("Removed rented role {} from {} in {}", rental.role_id, rental.user_id, rental.server_id),
+            // The member left or the role was deleted, so there is nothing to take away
+            Err(serenity::Error::Http(error)) if error.status_code() == Some(StatusCode::NOT_FOUND) => {}
+            // Kept around to try again on the next check
+            Err(error) => {
+                warn!("Couldn't remove rented role {} from {} in {}: {}", rental.role_id, rental.user_id, rental.server_id, error);
+                continue;
+            }
+        }
+
+        store.delete_role_rental(&rental).await?;
+    }
+
+    Ok(())
+}
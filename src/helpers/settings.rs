@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use chrono::Duration;
+use poise::ChoiceParameter;
+use super::{daily::parse_timezone, outcome::WeightedOutcomes, types::MyDuration};
+use super::store::PawStore;
+use super::structs::ServerSettings;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum SettingKey {
+    #[name = "steal_interval"]
+    StealInterval,
+    #[name = "gamble_interval"]
+    GambleInterval,
+    #[name = "steal_chance"]
+    StealChance,
+    #[name = "gamble_chance"]
+    GambleChance,
+    #[name = "gamble_critical_chance"]
+    GambleCriticalChance,
+    #[name = "gamble_partial_chance"]
+    GamblePartialChance,
+    #[name = "steal_critical_chance"]
+    StealCriticalChance,
+    #[name = "steal_partial_chance"]
+    StealPartialChance,
+    #[name = "steal_caught_chance"]
+    StealCaughtChance,
+    #[name = "ranking_mode"]
+    RankingMode,
+    #[name = "daily_reward"]
+    DailyReward,
+    #[name = "daily_streak_bonus"]
+    DailyStreakBonus,
+    #[name = "daily_streak_cap"]
+    DailyStreakCap,
+    #[name = "daily_grace"]
+    DailyGrace,
+    #[name = "daily_reset"]
+    DailyReset,
+    #[name = "timezone"]
+    Timezone,
+    #[name = "duration_style"]
+    DurationStyle,
+    #[name = "gamble_min_stake"]
+    GambleMinStake,
+    #[name = "gamble_max_stake"]
+    GambleMaxStake,
+    #[name = "gamble_max_stake_percent"]
+    GambleMaxStakePercent,
+    #[name = "gamble_payout_percent"]
+    GamblePayoutPercent
+}
+
+// 30 days, the longest any interval can be set to
+const MAX_INTERVAL_MINUTES: u32 = 30 * 24 * 60;
+
+// Applies a new value to the settings, or explains why it isn't allowed. Whether it
+// fits with the other settings is up to check_settings.
+pub fn apply_setting(settings: &mut ServerSettings, key: SettingKey, value: &str) -> Result<(), String> {
+    // Everything but the modes, styles, timezone and "off" is a whole number
+    let number = || value.trim().parse::<u32>()
+        .map_err(|_| format!("{} has to be a whole number", key.name()));
+    // Mirrors the CHECK constraints on server_settings
+    let between = |min: u32, max: u32| number().and_then(|number| match (min..=max).contains(&number) {
+        true => Ok(number as i32),
+        false => Err(format!("{} has to be between {} and {}", key.name(), min, max))
+    });
+    // Intervals are in minutes, from one minute up to 30 days
+    let minutes = || number().and_then(|number| match (1..=MAX_INTERVAL_MINUTES).contains(&number) {
+        true => Ok(MyDuration(Duration::minutes(number as i64))),
+        false => Err(format!("{} has to be between 1 minute and 30 days ({} minutes)", key.name(), MAX_INTERVAL_MINUTES))
+    });
+
+    let chance = match key {
+        SettingKey::StealInterval => {
+            settings.steal_interval = minutes()?;
+            return Ok(());
+        }
+        SettingKey::GambleInterval => {
+            settings.gamble_interval = minutes()?;
+            return Ok(());
+        }
+        SettingKey::RankingMode => {
+            settings.ranking_mode = value.trim().parse()?;
+            return Ok(());
+        }
+        SettingKey::DailyReward => {
+            settings.daily_reward = between(1, 1000)?;
+            return Ok(());
+        }
+        SettingKey::DailyStreakBonus => {
+            settings.daily_streak_bonus = between(0, 1000)?;
+            return Ok(());
+        }
+        SettingKey::DailyStreakCap => {
+            settings.daily_streak_cap = between(1, 365)?;
+            return Ok(());
+        }
+        SettingKey::DailyGrace => {
+            settings.daily_grace = minutes()?;
+            return Ok(());
+        }
+        SettingKey::DailyReset => {
+            settings.daily_reset = value.trim().parse()?;
+            return Ok(());
+        }
+        SettingKey::Timezone => {
+            settings.timezone = parse_timezone(value)?;
+            return Ok(());
+        }
+        SettingKey::DurationStyle => {
+            settings.duration_style = value.trim().parse()?;
+            return Ok(());
+        }
+        SettingKey::GambleMinStake | SettingKey::GambleMaxStake => {
+            let stake = between(1, 1_000_000)?;
+            match key {
+                SettingKey::GambleMinStake => settings.gamble_min_stake = stake,
+                _ => settings.gamble_max_stake = stake
+            }
+            return Ok(());
+        }
+        SettingKey::GambleMaxStakePercent => {
+            settings.gamble_max_stake_percent = if value.trim().eq_ignore_ascii_case("off") {None} else {Some(between(1, 100)?)};
+            return Ok(());
+        }
+        SettingKey::GamblePayoutPercent => {
+            settings.gamble_payout_percent = if value.trim().eq_ignore_ascii_case("off") {None} else {Some(between(1, 500)?)};
+            return Ok(());
+        }
+        SettingKey::StealChance => &mut settings.steal_chance,
+        SettingKey::GambleChance => &mut settings.gamble_chance,
+        SettingKey::GambleCriticalChance => &mut settings.gamble_critical_chance,
+        SettingKey::GamblePartialChance => &mut settings.gamble_partial_chance,
+        SettingKey::StealCriticalChance => &mut settings.steal_critical_chance,
+        SettingKey::StealPartialChance => &mut settings.steal_partial_chance,
+        SettingKey::StealCaughtChance => &mut settings.steal_caught_chance
+    };
+
+    *chance = between(0, 100)?;
+
+    Ok(())
+}
+
+// Settings that are fine on their own but not together
+pub fn check_settings(settings: &ServerSettings) -> Result<(), String> {
+    // The chances of each action share the same 100%
+    WeightedOutcomes::for_gamble(settings).map_err(|reason| format!("Gamble: {}", reason))?;
+    WeightedOutcomes::for_steal(settings).map_err(|reason| format!("Steal: {}", reason))?;
+
+    if settings.gamble_min_stake > settings.gamble_max_stake {
+        return Err(format!("gamble_min_stake ({}) can't be above gamble_max_stake ({})", settings.gamble_min_stake, settings.gamble_max_stake));
+    }
+
+    Ok(())
+}
+
+// A setting written the way apply_setting reads it back
+pub fn setting_value(settings: &ServerSettings, key: SettingKey) -> String {
+    let percent_or_off = |percent: Option<i32>| percent.map_or("off".to_string(), |percent| percent.to_string());
+
+    match key {
+        SettingKey::StealInterval => settings.steal_interval.duration().num_minutes().to_string(),
+        SettingKey::GambleInterval => settings.gamble_interval.duration().num_minutes().to_string(),
+        SettingKey::StealChance => settings.steal_chance.to_string(),
+        SettingKey::GambleChance => settings.gamble_chance.to_string(),
+        SettingKey::GambleCriticalChance => settings.gamble_critical_chance.to_string(),
+        SettingKey::GamblePartialChance => settings.gamble_partial_chance.to_string(),
+        SettingKey::StealCriticalChance => settings.steal_critical_chance.to_string(),
+        SettingKey::StealPartialChance => settings.steal_partial_chance.to_string(),
+        SettingKey::StealCaughtChance => settings.steal_caught_chance.to_string(),
+        SettingKey::RankingMode => settings.ranking_mode.as_str().to_string(),
+        SettingKey::DailyReward => settings.daily_reward.to_string(),
+        SettingKey::DailyStreakBonus => settings.daily_streak_bonus.to_string(),
+        SettingKey::DailyStreakCap => settings.daily_streak_cap.to_string(),
+        SettingKey::DailyGrace => settings.daily_grace.duration().num_minutes().to_string(),
+        SettingKey::DailyReset => settings.daily_reset.as_str().to_string(),
+        SettingKey::Timezone => settings.timezone.name().to_string(),
+        SettingKey::DurationStyle => settings.duration_style.as_str().to_string(),
+        SettingKey::GambleMinStake => settings.gamble_min_stake.to_string(),
+        SettingKey::GambleMaxStake => settings.gamble_max_stake.to_string(),
+        SettingKey::GambleMaxStakePercent => percent_or_off(settings.gamble_max_stake_percent),
+        SettingKey::GamblePayoutPercent => percent_or_off(settings.gamble_payout_percent)
+    }
+}
+
+// Keeps every server's settings in memory once they have been read. All writes
+// go through here so the cached copy is dropped whenever the database changes.
+#[derive(Debug)]
+pub struct SettingsCache {
+    defaults: ServerSettings,
+    servers: RwLock<HashMap<u64, ServerSettings>>
+}
+
+impl SettingsCache {
+    pub fn new(defaults: ServerSettings) -> SettingsCache {
+        SettingsCache {
+            defaults,
+            servers: RwLock::new(HashMap::new())
+        }
+    }
+
+    pub fn defaults(&self) -> &ServerSettings {
+        &self.defaults
+    }
+
+    pub async fn get(&self, store: &dyn PawStore, server_id: u64) -> Result<ServerSettings, Error> {
+        if let Some(settings) = self.servers.read().unwrap().get(&server_id) {
+            return Ok(settings.clone());
+        }
+
+        let settings = store.get_server_settings(server_id).await?
+            .unwrap_or_else(|| self.defaults.clone());
+
+        self.servers.write().unwrap().insert(server_id, settings.clone());
+
+        Ok(settings)
+    }
+
+    pub async fn set(&self, store: &dyn PawStore, server_id: u64, settings: &ServerSettings) -> Result<(), Error> {
+        store.upsert_server_settings(server_id, settings).await?;
+        self.invalidate(server_id);
+
+        Ok(())
+    }
+
+    // Returns true if the server had settings to remove
+    pub async fn reset(&self, store: &dyn PawStore, server_id: u64) -> Result<bool, Error> {
+        let removed = store.delete_server_settings(server_id).await?;
+        self.invalidate(server_id);
+
+        Ok(removed)
+    }
+
+    pub fn invalidate(&self, server_id: u64) {
+        self.servers.write().unwrap().remove(&server_id);
+    }
+}
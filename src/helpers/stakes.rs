@@ -0,0 +1,107 @@
+use super::structs::ServerSettings;
+
+// The most a member can stake right now: the server's cap, the share of their
+// balance the server allows, and never more than they actually hold
+pub fn max_stake(settings: &ServerSettings, balance: u64) -> u64 {
+    let mut max = std::cmp::min(settings.gamble_max_stake.max(1) as u64, balance);
+
+    if let Some(percent) = settings.gamble_max_stake_percent {
+        max = std::cmp::min(max, balance.saturating_mul(percent.clamp(1, 100) as u64) / 100);
+    }
+
+    max
+}
+
+// Explains why a stake can't be played, if it can't
+pub fn check_stake(settings: &ServerSettings, stake: u64, balance: u64) -> Result<(), String> {
+    let min = settings.gamble_min_stake.max(1) as u64;
+    let max = max_stake(settings, balance);
+    let paw_word = |count: u64| if count != 1 {"paws"} else {"paw"};
+
+    if max < min {
+        return Err(format!("You need more paws to gamble here, the smallest stake is {} {}", min, paw_word(min)));
+    }
+
+    if stake < min || stake > max {
+        return Err(format!("You can stake {} to {} {} right now", min, max, paw_word(max)));
+    }
+
+    Ok(())
+}
+
+// What a win pays on top of the stake for a bet won chance percent of the time.
+// Wins are priced against the odds so that gamble_payout_percent of all stakes
+// comes back in the long run, or all of them when it's off, e.g. at 100% a 25%
+// chance pays 4x the stake (3x on top of it). Rounds down, so small stakes on
+// likely bets can win nothing.
+pub fn winnings_at(settings: &ServerSettings, stake: u64, chance: u32) -> u64 {
+    let payout = settings.gamble_payout_percent.unwrap_or(100).max(1) as u64;
+    let returned = stake.saturating_mul(payout) / chance.max(1) as u64;
+
+    returned.saturating_sub(stake)
+}
+
+// What a plain win of /paw gamble pays on top of the stake, even money unless a
+// payout percent is set
+pub fn winnings(settings: &ServerSettings, stake: u64) -> u64 {
+    match settings.gamble_payout_percent {
+        Some(_) if settings.gamble_chance > 0 => winnings_at(settings, stake, settings.gamble_chance as u32),
+        _ => stake
+    }
+}
+
+// How many times the stake a win at chance percent returns, for showing players the odds
+pub fn multiplier_at(settings: &ServerSettings, chance: u32) -> f64 {
+    settings.gamble_payout_percent.unwrap_or(100).max(1) as f64 / chance.max(1) as f64
+}
+
+// Same for a plain win of /paw gamble
+pub fn multiplier(settings: &ServerSettings) -> f64 {
+    match settings.gamble_payout_percent {
+        Some(_) if settings.gamble_chance > 0 => multiplier_at(settings, settings.gamble_chance as u32),
+        _ => 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stake_limits() {
+        let settings = ServerSettings { gamble_min_stake: 5, gamble_max_stake: 20, ..ServerSettings::default() };
+        assert_eq!(max_stake(&settings, 100), 20);
+        assert_eq!(max_stake(&settings, 8), 8);
+
+        assert!(check_stake(&settings, 5, 100).is_ok());
+        assert!(check_stake(&settings, 20, 100).is_ok());
+        assert!(check_stake(&settings, 4, 100).is_err());
+        assert!(check_stake(&settings, 21, 100).is_err());
+        assert!(check_stake(&settings, 9, 8).is_err());
+        assert_eq!(check_stake(&settings, 5, 4), Err("You need more paws to gamble here, the smallest stake is 5 paws".to_string()));
+    }
+
+    #[test]
+    fn stake_percent() {
+        let settings = ServerSettings { gamble_max_stake: 1000, gamble_max_stake_percent: Some(10), ..ServerSettings::default() };
+        assert_eq!(max_stake(&settings, 500), 50);
+        assert_eq!(check_stake(&settings, 2, 9), Err("You need more paws to gamble here, the smallest stake is 1 paw".to_string()));
+    }
+
+    #[test]
+    fn winnings_follow_the_odds() {
+        // Even money while the payout percent is off
+        let settings = ServerSettings::default();
+        assert_eq!(winnings(&settings, 10), 10);
+        assert_eq!(multiplier(&settings), 2.0);
+        assert_eq!(winnings_at(&settings, 10, 25), 30);
+        assert_eq!(winnings_at(&settings, 10, 50), 10);
+
+        let settings = ServerSettings { gamble_chance: 25, gamble_payout_percent: Some(90), ..ServerSettings::default() };
+        assert_eq!(winnings(&settings, 10), 26);
+        assert_eq!(multiplier(&settings), 3.6);
+
+        // Rounded down to nothing on small stakes at likely odds
+        assert_eq!(winnings_at(&settings, 1, 90), 0);
+    }
+}
@@ -0,0 +1,867 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use super::PawStore;
+use super::super::migrations::MigrationStatus;
+use super::super::structs::*;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// Keeps everything in process memory. Nothing survives a restart, which is what
+// you want for tests and trying the bot out without a database.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    data: Mutex<MemoryData>
+}
+
+// Users are keyed by (server_id, user_id)
+#[derive(Debug, Default)]
+struct MemoryData {
+    paw_counts: HashMap<(u64, u64), u64>,
+    user_limits: HashMap<(u64, u64), UserLimits>,
+    server_settings: HashMap<u64, ServerSettings>,
+    user_profiles: HashMap<u64, UserProfile>,
+    shop_items: Vec<ShopItem>,
+    // Keyed by (server_id, user_id, item_id)
+    user_items: HashMap<(u64, u64, i64), u64>,
+    next_item_id: i64,
+    // Keyed by (server_id, role_id)
+    role_listings: HashMap<(u64, u64), RoleListing>,
+    // Keyed by (server_id, user_id, role_id)
+    role_rentals: HashMap<(u64, u64, u64), DateTime<Utc>>,
+    // Keyed by (server_id, user_id, action)
+    reminders: HashMap<(u64, u64, ReminderAction), Reminder>,
+    game_results: Vec<GameResult>,
+    open_games: Vec<OpenGame>,
+    duels: Vec<Duel>,
+    next_duel_id: i64,
+    // Keyed by snapshot id, with the server and every balance above zero
+    snapshots: HashMap<i64, (u64, Vec<(u64, u64)>)>,
+    audit_log: Vec<AuditEntry>,
+    // Oldest first, ids count up from 1
+    ledger: Vec<LedgerEntry>
+}
+
+impl MemoryData {
+    // Every farmer on a server, most paws first
+    fn leaderboard(&self, server_id: u64) -> Vec<PawCount> {
+        let mut leaderboard: Vec<PawCount> = self.paw_counts.iter()
+            .filter(|((server, _), _)| *server == server_id)
+            .map(|((_, user_id), count)| PawCount { count: *count as i64, user_id: *user_id as i64 })
+            .collect();
+
+        leaderboard.sort_by(|a, b| b.count.cmp(&a.count).then(a.user_id.cmp(&b.user_id)));
+        leaderboard
+    }
+
+    // Same rules as transfer_paws, for callers already holding the lock
+    fn transfer(&mut self, transfer: &Transfer) -> Result<TransferResult, Error> {
+        if let Some(source_id) = transfer.source_id {
+            let available = self.paw_counts.get(&(transfer.server_id, source_id)).copied().unwrap_or(0);
+            if available < transfer.amount {
+                return Ok(TransferResult::InsufficientFunds { available });
+            }
+        }
+
+        if let Some(target_id) = transfer.target_id {
+            let held = self.paw_counts.get(&(transfer.server_id, target_id)).copied().unwrap_or(0);
+            if held.checked_add(transfer.amount).is_none_or(|count| count > MAX_PAWS) {
+                return Err("That would leave a balance with more paws than can be counted".into());
+            }
+        }
+
+        let source_count = transfer.source_id.map(|source_id| {
+            let count = self.paw_counts.entry((transfer.server_id, source_id)).or_insert(0);
+            *count -= transfer.amount;
+            *count
+        });
+
+        let target_count = transfer.target_id.map(|target_id| {
+            let count = self.paw_counts.entry((transfer.server_id, target_id)).or_insert(0);
+            *count += transfer.amount;
+            *count
+        });
+
+        self.ledger.push(LedgerEntry {
+            id: self.ledger.len() as i64 + 1,
+            server_id: transfer.server_id,
+            actor_id: transfer.actor_id,
+            source_id: transfer.source_id,
+            target_id: transfer.target_id,
+            amount: transfer.amount,
+            reason: transfer.reason,
+            created_at: Utc::now()
+        });
+
+        Ok(TransferResult::Completed { source_count, target_count })
+    }
+
+    // Cheapest first, like the shop
+    fn inventory(&self, user_id: u64, server_id: u64) -> Vec<InventoryItem> {
+        let mut inventory: Vec<InventoryItem> = self.shop_items.iter()
+            .filter(|item| item.server_id == server_id)
+            .filter_map(|item| {
+                let quantity = self.user_items.get(&(server_id, user_id, item.id)).copied()?;
+                Some(InventoryItem { item: item.clone(), quantity })
+            })
+            .collect();
+
+        inventory.sort_by(|a, b| a.item.price.cmp(&b.item.price).then(a.item.name.cmp(&b.item.name)));
+        inventory
+    }
+}
+
+#[async_trait]
+impl PawStore for MemoryStore {
+    async fn migrate(&self) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    // There's no schema, so it's always current
+    async fn migration_status(&self) -> Result<MigrationStatus, Error> {
+        Ok(MigrationStatus { current_version: 0, latest_version: 0, pending: Vec::new() })
+    }
+
+    async fn get_paw_count(&self, user_id: u64, server_id: u64) -> Result<u64, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.paw_counts.get(&(server_id, user_id)).copied().unwrap_or(0))
+    }
+
+    async fn transfer_paws(&self, transfer: &Transfer) -> Result<TransferResult, Error> {
+        // Holding the lock for the whole transfer makes it atomic
+        let mut data = self.data.lock().unwrap();
+        data.transfer(transfer)
+    }
+
+    async fn get_ledger(&self, server_id: u64, filter: LedgerFilter, offset: u64, limit: u64) -> Result<Vec<LedgerEntry>, Error> {
+        let data = self.data.lock().unwrap();
+
+        Ok(data.ledger.iter()
+            .rev()
+            .filter(|entry| entry.server_id == server_id && filter.matches(entry))
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn count_ledger(&self, server_id: u64, filter: LedgerFilter) -> Result<u64, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.ledger.iter().filter(|entry| entry.server_id == server_id && filter.matches(entry)).count() as u64)
+    }
+
+    async fn set_paw_count(&self, user_id: u64, server_id: u64, actor_id: u64, count: u64) -> Result<u64, Error> {
+        let mut data = self.data.lock().unwrap();
+        let previous = data.paw_counts.get(&(server_id, user_id)).copied().unwrap_or(0);
+
+        if let Some(transfer) = Transfer::set_balance(server_id, user_id, actor_id, previous, count, LedgerReason::AdminSet) {
+            data.transfer(&transfer)?;
+        }
+
+        Ok(previous)
+    }
+
+    async fn reset_economy(&self, server_id: u64, actor_id: u64, now: DateTime<Utc>) -> Result<EconomyReset, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let mut balances: Vec<(u64, u64)> = data.paw_counts.iter()
+            .filter(|((server, _), count)| *server == server_id && **count > 0)
+            .map(|((_, user_id), count)| (*user_id, *count))
+            .collect();
+        balances.sort_unstable();
+
+        let first_entry = data.ledger.len();
+        for (user_id, count) in &balances {
+            data.transfer(&Transfer {
+                server_id,
+                actor_id,
+                source_id: Some(*user_id),
+                target_id: None,
+                amount: *count,
+                reason: LedgerReason::AdminReset
+            })?;
+        }
+        data.paw_counts.retain(|(server, _), _| *server != server_id);
+        for entry in &mut data.ledger[first_entry..] {
+            entry.created_at = now;
+        }
+
+        let snapshot_id = data.snapshots.len() as i64 + 1;
+        let reset = EconomyReset {
+            snapshot_id,
+            farmers: balances.len() as u64,
+            paws: balances.iter().fold(0, |paws: u64, (_, count)| paws.saturating_add(*count))
+        };
+        data.snapshots.insert(snapshot_id, (server_id, balances));
+
+        Ok(reset)
+    }
+
+    async fn export_economy(&self, server_id: u64) -> Result<EconomyData, Error> {
+        let data = self.data.lock().unwrap();
+
+        let mut balances: Vec<(u64, u64)> = data.paw_counts.iter()
+            .filter(|((server, _), _)| *server == server_id)
+            .map(|((_, user_id), count)| (*user_id, *count))
+            .collect();
+        balances.sort_unstable();
+
+        let mut limits: Vec<(u64, UserLimits)> = data.user_limits.iter()
+            .filter(|((server, _), _)| *server == server_id)
+            .map(|((_, user_id), limits)| (*user_id, limits.clone()))
+            .collect();
+        limits.sort_unstable_by_key(|(user_id, _)| *user_id);
+
+        Ok(EconomyData {
+            balances,
+            limits,
+            settings: data.server_settings.get(&server_id).cloned()
+        })
+    }
+
+    async fn import_economy(&self, server_id: u64, actor_id: u64, import: &EconomyData, mode: ImportMode) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let current: Vec<(u64, u64)> = data.paw_counts.iter()
+            .filter(|((server, _), _)| *server == server_id)
+            .map(|((_, user_id), count)| (*user_id, *count))
+            .collect();
+
+        // Balances move through the ledger like any other change
+        for transfer in import.import_transfers(server_id, actor_id, &current, mode) {
+            let TransferResult::Completed { .. } = data.transfer(&transfer)? else {
+                return Err("A balance changed while it was being imported".into());
+            };
+        }
+
+        if mode == ImportMode::Replace {
+            // Everyone dropped has nothing left by now
+            data.paw_counts.retain(|(server, _), count| *server != server_id || *count > 0);
+            data.user_limits.retain(|(server, _), _| *server != server_id);
+            data.server_settings.remove(&server_id);
+        }
+
+        for (user_id, limits) in &import.limits {
+            data.user_limits.insert((server_id, *user_id), limits.clone());
+        }
+        if let Some(settings) = &import.settings {
+            data.server_settings.insert(server_id, settings.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn add_audit_entry(&self, entry: &AuditEntry) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        let id = data.audit_log.len() as i64 + 1;
+        data.audit_log.push(AuditEntry { id, ..entry.clone() });
+        Ok(())
+    }
+
+    async fn get_audit_log(&self, server_id: u64, limit: u64) -> Result<Vec<AuditEntry>, Error> {
+        let data = self.data.lock().unwrap();
+
+        Ok(data.audit_log.iter()
+            .rev()
+            .filter(|entry| entry.server_id == server_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_last_used(&self, user_id: u64, server_id: u64, cooldown: Cooldown) -> Result<Option<DateTime<Utc>>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.user_limits.get(&(server_id, user_id)).and_then(|limits| limits.last_used(cooldown)))
+    }
+
+    async fn claim_cooldown(&self, user_id: u64, server_id: u64, cooldown: Cooldown, previous: Option<DateTime<Utc>>, time: DateTime<Utc>) -> Result<bool, Error> {
+        let mut data = self.data.lock().unwrap();
+        let limits = data.user_limits.entry((server_id, user_id)).or_default();
+        if limits.last_used(cooldown) != previous {
+            return Ok(false);
+        }
+
+        limits.set_last_used(cooldown, time);
+        Ok(true)
+    }
+
+    async fn get_daily_streak(&self, user_id: u64, server_id: u64) -> Result<DailyStreak, Error> {
+        let data = self.data.lock().unwrap();
+        let limits = data.user_limits.get(&(server_id, user_id)).cloned().unwrap_or_default();
+
+        Ok(DailyStreak {
+            last_claimed: limits.last_daily,
+            current: limits.daily_streak as u32,
+            best: limits.best_daily_streak as u32
+        })
+    }
+
+    async fn claim_daily(&self, claim: &DailyClaim) -> Result<Option<u64>, Error> {
+        let mut data = self.data.lock().unwrap();
+        let limits = data.user_limits.entry((claim.server_id, claim.user_id)).or_default();
+        if limits.last_daily != claim.previous {
+            return Ok(None);
+        }
+
+        limits.last_daily = Some(claim.claimed_at);
+        limits.daily_streak = claim.streak as i32;
+        limits.best_daily_streak = claim.best as i32;
+
+        match data.transfer(&claim.transfer())? {
+            TransferResult::Completed { target_count, .. } => Ok(target_count),
+            TransferResult::InsufficientFunds { .. } => Err("The house can't run out of paws".into())
+        }
+    }
+
+    async fn place_stake(&self, stake: &GameStake) -> Result<Option<TransferResult>, Error> {
+        let mut data = self.data.lock().unwrap();
+        let key = (stake.server_id, stake.user_id);
+        if data.user_limits.get(&key).and_then(|limits| limits.last_gamble) != stake.previous {
+            return Ok(None);
+        }
+
+        // Players who can't cover the stake keep their cooldown
+        let held = data.transfer(&stake.transfer())?;
+        if let TransferResult::Completed { .. } = held {
+            data.user_limits.entry(key).or_default().last_gamble = Some(stake.placed_at);
+            data.open_games.push(stake.open_game());
+        }
+
+        Ok(Some(held))
+    }
+
+    async fn settle_game(&self, result: &GameResult) -> Result<Option<u64>, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let open = data.open_games.iter().position(|game| {
+            (game.server_id, game.user_id, game.placed_at) == (result.server_id, result.user_id, result.played_at)
+        });
+        let Some(open) = open else {
+            return Ok(None);
+        };
+
+        let balance = match result.transfer() {
+            Some(transfer) => match data.transfer(&transfer)? {
+                TransferResult::Completed { target_count, .. } => target_count.unwrap_or(0),
+                TransferResult::InsufficientFunds { .. } => return Err("The house can't run out of paws".into())
+            },
+            None => data.paw_counts.get(&(result.server_id, result.user_id)).copied().unwrap_or(0)
+        };
+        data.open_games.remove(open);
+        data.game_results.push(result.clone());
+
+        Ok(Some(balance))
+    }
+
+    async fn refund_game(&self, game: &OpenGame) -> Result<Option<u64>, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let Some(open) = data.open_games.iter().position(|open| open == game) else {
+            return Ok(None);
+        };
+
+        let TransferResult::Completed { target_count, .. } = data.transfer(&game.refund())? else {
+            return Err("The house can't run out of paws".into());
+        };
+        data.open_games.remove(open);
+
+        Ok(target_count)
+    }
+
+    async fn get_open_games(&self, placed_before: DateTime<Utc>) -> Result<Vec<OpenGame>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.open_games.iter().filter(|game| game.placed_at < placed_before).copied().collect())
+    }
+
+    async fn get_game_stats(&self, server_id: u64) -> Result<Vec<GameStats>, Error> {
+        let data = self.data.lock().unwrap();
+        let mut stats: Vec<GameStats> = Vec::new();
+
+        for result in data.game_results.iter().filter(|result| result.server_id == server_id) {
+            let index = match stats.iter().position(|stats| stats.game == result.game) {
+                Some(index) => index,
+                None => {
+                    stats.push(GameStats { game: result.game, played: 0, staked: 0, payout: 0 });
+                    stats.len() - 1
+                }
+            };
+
+            stats[index].played += 1;
+            stats[index].staked += result.stake;
+            stats[index].payout += result.payout;
+        }
+
+        stats.sort_by_key(|stats| stats.game.as_str());
+        Ok(stats)
+    }
+
+    async fn open_duel(&self, duel: &Duel) -> Result<Option<i64>, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        if let TransferResult::InsufficientFunds { .. } = data.transfer(&duel.escrow())? {
+            return Ok(None);
+        }
+
+        data.next_duel_id += 1;
+        let id = data.next_duel_id;
+        data.duels.push(Duel { id, ..duel.clone() });
+
+        Ok(Some(id))
+    }
+
+    async fn accept_duel(&self, duel: &Duel, winner_id: u64) -> Result<DuelResult, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let Some(index) = data.duels.iter().position(|open| open.id == duel.id) else {
+            return Ok(DuelResult::Closed);
+        };
+
+        if let TransferResult::InsufficientFunds { available } = data.transfer(&duel.accept())? {
+            return Ok(DuelResult::InsufficientFunds { available });
+        }
+        data.duels.remove(index);
+
+        let TransferResult::Completed { target_count: Some(winner_count), .. } = data.transfer(&duel.payout(winner_id))? else {
+            return Err("The house can't run out of paws".into());
+        };
+
+        let loser_id = if winner_id == duel.challenger_id {duel.target_id} else {duel.challenger_id};
+        let loser_count = data.paw_counts.get(&(duel.server_id, loser_id)).copied().unwrap_or(0);
+
+        Ok(DuelResult::Fought { winner_count, loser_count })
+    }
+
+    async fn cancel_duel(&self, duel: &Duel) -> Result<Option<u64>, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let Some(index) = data.duels.iter().position(|open| open.id == duel.id) else {
+            return Ok(None);
+        };
+        data.duels.remove(index);
+
+        match data.transfer(&duel.refund())? {
+            TransferResult::Completed { target_count, .. } => Ok(target_count),
+            TransferResult::InsufficientFunds { .. } => Err("The house can't run out of paws".into())
+        }
+    }
+
+    async fn get_expired_duels(&self, now: DateTime<Utc>) -> Result<Vec<Duel>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.duels.iter().filter(|duel| duel.expires_at <= now).cloned().collect())
+    }
+
+    async fn get_server_settings(&self, server_id: u64) -> Result<Option<ServerSettings>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.server_settings.get(&server_id).cloned())
+    }
+
+    async fn upsert_server_settings(&self, server_id: u64, settings: &ServerSettings) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        data.server_settings.insert(server_id, settings.clone());
+        Ok(())
+    }
+
+    async fn delete_server_settings(&self, server_id: u64) -> Result<bool, Error> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data.server_settings.remove(&server_id).is_some())
+    }
+
+    async fn get_leaderboard(&self, server_id: u64, offset: u64, limit: u64) -> Result<Vec<PawCount>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.leaderboard(server_id).into_iter().skip(offset as usize).take(limit as usize).collect())
+    }
+
+    async fn get_leaderboard_totals(&self, server_id: u64) -> Result<(u64, u64), Error> {
+        let data = self.data.lock().unwrap();
+        let leaderboard = data.leaderboard(server_id);
+
+        Ok((leaderboard.len() as u64, leaderboard.iter().map(|farmer| farmer.count as u64).sum()))
+    }
+
+    async fn count_farmers_above(&self, server_id: u64, count: u64, distinct: bool) -> Result<u64, Error> {
+        let data = self.data.lock().unwrap();
+        let mut above: Vec<i64> = data.leaderboard(server_id).iter()
+            .map(|farmer| farmer.count)
+            .filter(|farmer_count| *farmer_count as u64 > count)
+            .collect();
+
+        // Already sorted, so duplicates sit next to each other
+        if distinct {
+            above.dedup();
+        }
+
+        Ok(above.len() as u64)
+    }
+
+    async fn count_tied_before(&self, server_id: u64, count: u64, user_id: u64) -> Result<u64, Error> {
+        let data = self.data.lock().unwrap();
+        let tied = data.leaderboard(server_id).iter()
+            .filter(|farmer| farmer.count as u64 == count && (farmer.user_id as u64) < user_id)
+            .count();
+
+        Ok(tied as u64)
+    }
+
+    async fn get_user_profiles(&self, user_ids: &[u64]) -> Result<Vec<UserProfile>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(user_ids.iter().filter_map(|user_id| data.user_profiles.get(user_id).cloned()).collect())
+    }
+
+    async fn upsert_user_profiles(&self, profiles: &[UserProfile]) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        for profile in profiles {
+            data.user_profiles.insert(profile.user_id as u64, profile.clone());
+        }
+        Ok(())
+    }
+
+    async fn get_shop_items(&self, server_id: u64) -> Result<Vec<ShopItem>, Error> {
+        let data = self.data.lock().unwrap();
+        let mut items: Vec<ShopItem> = data.shop_items.iter()
+            .filter(|item| item.server_id == server_id)
+            .cloned()
+            .collect();
+
+        items.sort_by(|a, b| a.price.cmp(&b.price).then(a.name.cmp(&b.name)));
+        Ok(items)
+    }
+
+    async fn upsert_shop_item(&self, item: &ShopItem) -> Result<ShopItem, Error> {
+        let mut data = self.data.lock().unwrap();
+
+        let existing = data.shop_items.iter_mut().find(|existing| existing.server_id == item.server_id && existing.name == item.name);
+        if let Some(existing) = existing {
+            *existing = ShopItem { id: existing.id, ..item.clone() };
+            return Ok(existing.clone());
+        }
+
+        data.next_item_id += 1;
+        let item = ShopItem { id: data.next_item_id, ..item.clone() };
+        data.shop_items.push(item.clone());
+        Ok(item)
+    }
+
+    async fn delete_shop_item(&self, server_id: u64, name: &str) -> Result<bool, Error> {
+        let mut data = self.data.lock().unwrap();
+        let Some(index) = data.shop_items.iter().position(|item| item.server_id == server_id && item.name == name) else {
+            return Ok(false);
+        };
+
+        // Held items go with it, like the foreign key in the database
+        let item = data.shop_items.remove(index);
+        data.user_items.retain(|(_, _, item_id), _| *item_id != item.id);
+        Ok(true)
+    }
+
+    async fn buy_item(&self, user_id: u64, item: &ShopItem, quantity: u64) -> Result<TransferResult, Error> {
+        let mut data = self.data.lock().unwrap();
+        if !data.shop_items.iter().any(|listed| listed.id == item.id) {
+            return Err("That item is no longer for sale".into());
+        }
+
+        let amount = item.price.checked_mul(quantity).ok_or("Purchase is too expensive")?;
+        let result = data.transfer(&Transfer {
+            server_id: item.server_id,
+            actor_id: user_id,
+            source_id: Some(user_id),
+            target_id: None,
+            amount,
+            reason: LedgerReason::ShopPurchase
+        })?;
+
+        if let TransferResult::Completed { .. } = result {
+            *data.user_items.entry((item.server_id, user_id, item.id)).or_insert(0) += quantity;
+        }
+
+        Ok(result)
+    }
+
+    async fn get_inventory(&self, user_id: u64, server_id: u64) -> Result<Vec<InventoryItem>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.inventory(user_id, server_id))
+    }
+
+    async fn consume_item(&self, user_id: u64, server_id: u64, effect: ItemEffect) -> Result<Option<ShopItem>, Error> {
+        let mut data = self.data.lock().unwrap();
+        let strongest = data.inventory(user_id, server_id).into_iter()
+            .filter(|held| held.item.effect == effect)
+            .max_by(|a, b| a.item.strength.cmp(&b.item.strength).then(b.item.id.cmp(&a.item.id)));
+
+        let Some(held) = strongest else {
+            return Ok(None);
+        };
+
+        let key = (server_id, user_id, held.item.id);
+        if held.quantity > 1 {
+            data.user_items.insert(key, held.quantity - 1);
+        } else {
+            data.user_items.remove(&key);
+        }
+
+        Ok(Some(held.item))
+    }
+
+    async fn get_role_listings(&self, server_id: u64) -> Result<Vec<RoleListing>, Error> {
+        let data = self.data.lock().unwrap();
+        let mut listings: Vec<RoleListing> = data.role_listings.values()
+            .filter(|listing| listing.server_id == server_id)
+            .cloned()
+            .collect();
+
+        listings.sort_by(|a, b| a.price.cmp(&b.price).then(a.role_id.cmp(&b.role_id)));
+        Ok(listings)
+    }
+
+    async fn upsert_role_listing(&self, listing: &RoleListing) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        data.role_listings.insert((listing.server_id, listing.role_id), listing.clone());
+        Ok(())
+    }
+
+    async fn delete_role_listing(&self, server_id: u64, role_id: u64) -> Result<bool, Error> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data.role_listings.remove(&(server_id, role_id)).is_some())
+    }
+
+    async fn buy_role(&self, user_id: u64, listing: &RoleListing, now: DateTime<Utc>) -> Result<TransferResult, Error> {
+        let mut data = self.data.lock().unwrap();
+        let result = data.transfer(&Transfer {
+            server_id: listing.server_id,
+            actor_id: user_id,
+            source_id: Some(user_id),
+            target_id: None,
+            amount: listing.price,
+            reason: LedgerReason::RolePurchase
+        })?;
+
+        if let TransferResult::Completed { .. } = result {
+            let key = (listing.server_id, user_id, listing.role_id);
+            match listing.rental_end(data.role_rentals.get(&key).copied(), now) {
+                Some(expires_at) => data.role_rentals.insert(key, expires_at),
+                // Held for good now, so the expiry job mustn't take it away
+                None => data.role_rentals.remove(&key)
+            };
+        }
+
+        Ok(result)
+    }
+
+    async fn get_role_rentals(&self, server_id: u64, user_id: u64) -> Result<Vec<RoleRental>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.role_rentals.iter()
+            .filter(|((server, user, _), _)| *server == server_id && *user == user_id)
+            .map(|((server_id, user_id, role_id), expires_at)| RoleRental { server_id: *server_id, user_id: *user_id, role_id: *role_id, expires_at: *expires_at })
+            .collect())
+    }
+
+    async fn get_expired_role_rentals(&self, now: DateTime<Utc>) -> Result<Vec<RoleRental>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.role_rentals.iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|((server_id, user_id, role_id), expires_at)| RoleRental { server_id: *server_id, user_id: *user_id, role_id: *role_id, expires_at: *expires_at })
+            .collect())
+    }
+
+    async fn delete_role_rental(&self, rental: &RoleRental) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        let key = (rental.server_id, rental.user_id, rental.role_id);
+
+        // Leave it alone if it was extended since it was read
+        if data.role_rentals.get(&key).is_some_and(|expires_at| *expires_at <= rental.expires_at) {
+            data.role_rentals.remove(&key);
+        }
+        Ok(())
+    }
+
+    async fn get_reminders(&self, server_id: u64, user_id: u64) -> Result<Vec<Reminder>, Error> {
+        let data = self.data.lock().unwrap();
+        let mut reminders: Vec<Reminder> = data.reminders.values()
+            .filter(|reminder| reminder.server_id == server_id && reminder.user_id == user_id)
+            .cloned()
+            .collect();
+
+        reminders.sort_by_key(|reminder| reminder.action.as_str());
+        Ok(reminders)
+    }
+
+    async fn upsert_reminder(&self, reminder: &Reminder) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        data.reminders.insert((reminder.server_id, reminder.user_id, reminder.action), reminder.clone());
+        Ok(())
+    }
+
+    async fn delete_reminder(&self, server_id: u64, user_id: u64, action: ReminderAction) -> Result<bool, Error> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data.reminders.remove(&(server_id, user_id, action)).is_some())
+    }
+
+    async fn schedule_reminder(&self, server_id: u64, user_id: u64, action: ReminderAction, due_at: DateTime<Utc>) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        if let Some(reminder) = data.reminders.get_mut(&(server_id, user_id, action)) {
+            reminder.due_at = Some(due_at);
+        }
+        Ok(())
+    }
+
+    async fn get_due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(data.reminders.values()
+            .filter(|reminder| reminder.due_at.is_some_and(|due_at| due_at <= now))
+            .cloned()
+            .collect())
+    }
+
+    async fn clear_reminder(&self, reminder: &Reminder) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        let key = (reminder.server_id, reminder.user_id, reminder.action);
+
+        if let Some(stored) = data.reminders.get_mut(&key) {
+            if stored.due_at.is_some_and(|due_at| Some(due_at) <= reminder.due_at) {
+                stored.due_at = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::daily;
+
+    fn give(server_id: u64, source_id: Option<u64>, target_id: Option<u64>, amount: u64) -> Transfer {
+        Transfer { server_id, actor_id: 1, source_id, target_id, amount, reason: LedgerReason::Give }
+    }
+
+    async fn ledger(store: &MemoryStore, reason: LedgerReason) -> Vec<LedgerEntry> {
+        store.get_ledger(1, LedgerFilter { user_id: None, reason: Some(reason) }, 0, 100).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn transfers() {
+        let store = MemoryStore::default();
+        store.transfer_paws(&give(1, None, Some(1), 10)).await.unwrap();
+
+        let moved = store.transfer_paws(&give(1, Some(1), Some(2), 4)).await.unwrap();
+        assert_eq!(moved, TransferResult::Completed { source_count: Some(6), target_count: Some(4) });
+
+        // Nothing moves when the source can't cover it
+        let refused = store.transfer_paws(&give(1, Some(1), Some(2), 7)).await.unwrap();
+        assert_eq!(refused, TransferResult::InsufficientFunds { available: 6 });
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 6);
+        assert_eq!(store.get_paw_count(2, 1).await.unwrap(), 4);
+        assert_eq!(ledger(&store, LedgerReason::Give).await.len(), 2);
+
+        // Balances are per server
+        assert_eq!(store.get_paw_count(1, 2).await.unwrap(), 0);
+
+        // Nor when the target would hold more than can be counted
+        store.set_paw_count(3, 1, 1, MAX_PAWS).await.unwrap();
+        assert!(store.transfer_paws(&give(1, Some(1), Some(3), 1)).await.is_err());
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn daily_claims() {
+        let store = MemoryStore::default();
+        let settings = ServerSettings { daily_reward: 5, daily_streak_bonus: 1, ..ServerSettings::default() };
+        let now = Utc::now();
+
+        let streak = store.get_daily_streak(1, 1).await.unwrap();
+        let claim = daily::claim(&settings, 1, 1, &streak, now).unwrap();
+        assert_eq!(store.claim_daily(&claim).await.unwrap(), Some(5));
+
+        // A second claim read before the first went through is turned away
+        assert_eq!(store.claim_daily(&claim).await.unwrap(), None);
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 5);
+
+        let streak = store.get_daily_streak(1, 1).await.unwrap();
+        assert_eq!((streak.last_claimed, streak.current, streak.best), (Some(now), 1, 1));
+
+        let tomorrow = now + chrono::Duration::days(1);
+        let claim = daily::claim(&settings, 1, 1, &streak, tomorrow).unwrap();
+        assert_eq!(store.claim_daily(&claim).await.unwrap(), Some(11));
+        assert_eq!(ledger(&store, LedgerReason::Daily).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn open_games() {
+        let store = MemoryStore::default();
+        store.set_paw_count(1, 1, 1, 100).await.unwrap();
+        let now = Utc::now();
+
+        let stake = GameStake { server_id: 1, user_id: 1, stake: 30, previous: None, placed_at: now };
+        assert!(matches!(store.place_stake(&stake).await.unwrap(), Some(TransferResult::Completed { .. })));
+        assert_eq!(store.get_open_games(now + chrono::Duration::seconds(1)).await.unwrap(), [stake.open_game()]);
+
+        // Settled games can't be refunded as well
+        let result = GameResult { server_id: 1, user_id: 1, game: Game::Dice, stake: 30, payout: 10, played_at: now };
+        assert_eq!(store.settle_game(&result).await.unwrap(), Some(110));
+        assert_eq!(store.refund_game(&stake.open_game()).await.unwrap(), None);
+
+        // Nor refunded games settled
+        let later = now + chrono::Duration::minutes(1);
+        let stake = GameStake { stake: 50, previous: Some(now), placed_at: later, ..stake };
+        assert!(matches!(store.place_stake(&stake).await.unwrap(), Some(TransferResult::Completed { .. })));
+        assert_eq!(store.refund_game(&stake.open_game()).await.unwrap(), Some(110));
+        let result = GameResult { stake: 50, played_at: later, ..result };
+        assert_eq!(store.settle_game(&result).await.unwrap(), None);
+        assert_eq!(store.get_paw_count(1, 1).await.unwrap(), 110);
+        assert_eq!(ledger(&store, LedgerReason::GambleRefund).await.len(), 1);
+        assert!(store.get_open_games(later + chrono::Duration::seconds(1)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn resets() {
+        let store = MemoryStore::default();
+        for (user_id, count) in [(1, 100), (2, 50), (3, 0)] {
+            store.set_paw_count(user_id, 1, 1, count).await.unwrap();
+        }
+        store.set_paw_count(1, 2, 1, 30).await.unwrap();
+        let now = Utc::now();
+
+        // Everyone holding paws hands them back to the house, and is snapshotted first
+        let reset = store.reset_economy(1, 9, now).await.unwrap();
+        assert_eq!(reset, EconomyReset { snapshot_id: 1, farmers: 2, paws: 150 });
+        assert!(store.export_economy(1).await.unwrap().balances.is_empty());
+        assert_eq!(store.data.lock().unwrap().snapshots[&1], (1, vec![(1, 100), (2, 50)]));
+
+        let returned = ledger(&store, LedgerReason::AdminReset).await;
+        assert_eq!(returned.len(), 2);
+        assert!(returned.iter().all(|entry| entry.target_id.is_none() && entry.actor_id == 9 && entry.created_at == now));
+
+        // Other servers are untouched, and a second reset has nothing to take
+        assert_eq!(store.get_paw_count(1, 2).await.unwrap(), 30);
+        assert_eq!(store.reset_economy(1, 9, now).await.unwrap(), EconomyReset { snapshot_id: 2, farmers: 0, paws: 0 });
+    }
+
+    #[tokio::test]
+    async fn imports() {
+        let store = MemoryStore::default();
+        for (user_id, count) in [(1, 100), (2, 50), (3, 7)] {
+            store.set_paw_count(user_id, 1, 1, count).await.unwrap();
+        }
+        store.set_paw_count(1, 2, 1, 30).await.unwrap();
+
+        let limits = UserLimits { daily_streak: 2, best_daily_streak: 4, ..UserLimits::default() };
+        let import = EconomyData { balances: vec![(1, 40), (3, 7), (4, 9)], limits: vec![(4, limits.clone())], settings: None };
+
+        // Merging leaves everyone else alone, and only changes are recorded
+        store.import_economy(1, 9, &import, ImportMode::Merge).await.unwrap();
+        let exported = store.export_economy(1).await.unwrap();
+        assert_eq!(exported.balances, [(1, 40), (2, 50), (3, 7), (4, 9)]);
+        assert_eq!(exported.limits, [(4, limits.clone())]);
+        assert_eq!(ledger(&store, LedgerReason::Import).await.len(), 2);
+
+        // Replacing drops everyone missing, through the ledger as well
+        store.import_economy(1, 9, &import, ImportMode::Replace).await.unwrap();
+        let exported = store.export_economy(1).await.unwrap();
+        assert_eq!(exported.balances, [(1, 40), (3, 7), (4, 9)]);
+        let dropped = ledger(&store, LedgerReason::Import).await;
+        assert_eq!(dropped.len(), 3);
+        assert_eq!((dropped[0].source_id, dropped[0].amount, dropped[0].actor_id), (Some(2), 50, 9));
+
+        // Other servers are untouched
+        assert_eq!(store.get_paw_count(1, 2).await.unwrap(), 30);
+    }
+}
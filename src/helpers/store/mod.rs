@@ -0,0 +1,150 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use super::config::Config;
+use super::migrations::MigrationStatus;
+use super::structs::*;
+pub mod memory;
+pub mod postgres;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+// Everything the bot keeps between commands. Commands only talk to this trait,
+// so they run the same against Postgres or the in-memory store.
+#[async_trait]
+pub trait PawStore: std::fmt::Debug + Send + Sync {
+    // Brings the schema up to date, returning how many migrations were applied
+    async fn migrate(&self) -> Result<usize, Error>;
+    // Where the schema stands, without applying anything
+    async fn migration_status(&self) -> Result<MigrationStatus, Error>;
+
+    // Balances
+    async fn get_paw_count(&self, user_id: u64, server_id: u64) -> Result<u64, Error>;
+    async fn transfer_paws(&self, transfer: &Transfer) -> Result<TransferResult, Error>;
+
+    // The ledger of a server matching the filter, newest first
+    async fn get_ledger(&self, server_id: u64, filter: LedgerFilter, offset: u64, limit: u64) -> Result<Vec<LedgerEntry>, Error>;
+    async fn count_ledger(&self, server_id: u64, filter: LedgerFilter) -> Result<u64, Error>;
+
+    // Moves a balance to count through the ledger, returning what it was before
+    async fn set_paw_count(&self, user_id: u64, server_id: u64, actor_id: u64, count: u64) -> Result<u64, Error>;
+    // Snapshots every balance on the server, then takes them all back to the house
+    // in one transaction
+    async fn reset_economy(&self, server_id: u64, actor_id: u64, now: DateTime<Utc>) -> Result<EconomyReset, Error>;
+
+    // Everything an economy backup holds, ordered by user
+    async fn export_economy(&self, server_id: u64) -> Result<EconomyData, Error>;
+    // Writes a backup in one transaction, with every balance it changes in the
+    // ledger. Replacing drops every balance and limit not in it, and settings of
+    // None go back to the defaults.
+    async fn import_economy(&self, server_id: u64, actor_id: u64, data: &EconomyData, mode: ImportMode) -> Result<(), Error>;
+
+    // Audit log of /paw admin, newest first
+    async fn add_audit_entry(&self, entry: &AuditEntry) -> Result<(), Error>;
+    async fn get_audit_log(&self, server_id: u64, limit: u64) -> Result<Vec<AuditEntry>, Error>;
+
+    // Cooldowns, None if the user has never used the action
+    async fn get_last_used(&self, user_id: u64, server_id: u64, cooldown: Cooldown) -> Result<Option<DateTime<Utc>>, Error>;
+    // Starts a cooldown only while it was last used at previous, so two commands
+    // racing each other can't both go ahead. False if another one got in first.
+    async fn claim_cooldown(&self, user_id: u64, server_id: u64, cooldown: Cooldown, previous: Option<DateTime<Utc>>, time: DateTime<Utc>) -> Result<bool, Error>;
+
+    // Dailies, claims pay out and move the streak along in one transaction. The new
+    // balance is None if another claim got in first.
+    async fn get_daily_streak(&self, user_id: u64, server_id: u64) -> Result<DailyStreak, Error>;
+    async fn claim_daily(&self, claim: &DailyClaim) -> Result<Option<u64>, Error>;
+
+    // Games hold the stake and start the gamble cooldown in one transaction, None if
+    // another game started first. The game stays open until it's settled, which
+    // pays back what was won and records the result, giving the player's new
+    // balance. Results are matched to their game by played_at, the time the stake
+    // was placed, and settling gives None if the game was called off already.
+    async fn place_stake(&self, stake: &GameStake) -> Result<Option<TransferResult>, Error>;
+    async fn settle_game(&self, result: &GameResult) -> Result<Option<u64>, Error>;
+    // Closes a game and refunds the stake, None if it was already settled or refunded
+    async fn refund_game(&self, game: &OpenGame) -> Result<Option<u64>, Error>;
+    // Games on every server that were placed before a moment and are still open
+    async fn get_open_games(&self, placed_before: DateTime<Utc>) -> Result<Vec<OpenGame>, Error>;
+    // Totals of every game played on the server, by game name
+    async fn get_game_stats(&self, server_id: u64) -> Result<Vec<GameStats>, Error>;
+
+    // Duels hold the challenger's stake from the moment they are opened. Opening
+    // returns the new duel's id, or None if the challenger can't cover the stake.
+    async fn open_duel(&self, duel: &Duel) -> Result<Option<i64>, Error>;
+    // Takes the target's stake and pays both to the winner in one transaction
+    async fn accept_duel(&self, duel: &Duel, winner_id: u64) -> Result<DuelResult, Error>;
+    // Closes a duel and refunds the challenger, None if it was already closed
+    async fn cancel_duel(&self, duel: &Duel) -> Result<Option<u64>, Error>;
+    // Duels on every server that ran out before now
+    async fn get_expired_duels(&self, now: DateTime<Utc>) -> Result<Vec<Duel>, Error>;
+
+    // Settings, None for servers that have never changed them
+    async fn get_server_settings(&self, server_id: u64) -> Result<Option<ServerSettings>, Error>;
+    async fn upsert_server_settings(&self, server_id: u64, settings: &ServerSettings) -> Result<(), Error>;
+    async fn delete_server_settings(&self, server_id: u64) -> Result<bool, Error>;
+
+    // Leaderboards, most paws first with ties broken by user id
+    async fn get_leaderboard(&self, server_id: u64, offset: u64, limit: u64) -> Result<Vec<PawCount>, Error>;
+    // Number of farmers on the server and the paws they hold between them
+    async fn get_leaderboard_totals(&self, server_id: u64) -> Result<(u64, u64), Error>;
+    // Farmers holding more than count, or only the distinct counts above it
+    async fn count_farmers_above(&self, server_id: u64, count: u64, distinct: bool) -> Result<u64, Error>;
+    // Farmers holding exactly count that sort before user_id
+    async fn count_tied_before(&self, server_id: u64, count: u64, user_id: u64) -> Result<u64, Error>;
+
+    // Discord profiles are the same on every server, missing users are left out
+    async fn get_user_profiles(&self, user_ids: &[u64]) -> Result<Vec<UserProfile>, Error>;
+    async fn upsert_user_profiles(&self, profiles: &[UserProfile]) -> Result<(), Error>;
+
+    // Shop catalogue, cheapest first. Upserts match items by name and ignore the id.
+    async fn get_shop_items(&self, server_id: u64) -> Result<Vec<ShopItem>, Error>;
+    async fn upsert_shop_item(&self, item: &ShopItem) -> Result<ShopItem, Error>;
+    async fn delete_shop_item(&self, server_id: u64, name: &str) -> Result<bool, Error>;
+
+    // Pays for and hands over items in one transaction, the source count is the new balance
+    async fn buy_item(&self, user_id: u64, item: &ShopItem, quantity: u64) -> Result<TransferResult, Error>;
+    async fn get_inventory(&self, user_id: u64, server_id: u64) -> Result<Vec<InventoryItem>, Error>;
+    // Uses up one held item with the effect, strongest first
+    async fn consume_item(&self, user_id: u64, server_id: u64, effect: ItemEffect) -> Result<Option<ShopItem>, Error>;
+
+    // Roles for sale, one listing per role
+    async fn get_role_listings(&self, server_id: u64) -> Result<Vec<RoleListing>, Error>;
+    async fn upsert_role_listing(&self, listing: &RoleListing) -> Result<(), Error>;
+    async fn delete_role_listing(&self, server_id: u64, role_id: u64) -> Result<bool, Error>;
+    // Pays for a role in one transaction and starts or extends the rental, if it is
+    // one. Buying a role for good ends any rental of it.
+    async fn buy_role(&self, user_id: u64, listing: &RoleListing, now: DateTime<Utc>) -> Result<TransferResult, Error>;
+    async fn get_role_rentals(&self, server_id: u64, user_id: u64) -> Result<Vec<RoleRental>, Error>;
+    // Rentals on every server that ran out before now
+    async fn get_expired_role_rentals(&self, now: DateTime<Utc>) -> Result<Vec<RoleRental>, Error>;
+    async fn delete_role_rental(&self, rental: &RoleRental) -> Result<(), Error>;
+
+    // Reminders, one per member and action they opted in to
+    async fn get_reminders(&self, server_id: u64, user_id: u64) -> Result<Vec<Reminder>, Error>;
+    async fn upsert_reminder(&self, reminder: &Reminder) -> Result<(), Error>;
+    async fn delete_reminder(&self, server_id: u64, user_id: u64, action: ReminderAction) -> Result<bool, Error>;
+    // Sets when the next reminder goes out, does nothing for members who didn't opt in
+    async fn schedule_reminder(&self, server_id: u64, user_id: u64, action: ReminderAction, due_at: DateTime<Utc>) -> Result<(), Error>;
+    // Reminders on every server that were due by now
+    async fn get_due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>, Error>;
+    // Marks a reminder as sent, unless it was rescheduled since it was read
+    async fn clear_reminder(&self, reminder: &Reminder) -> Result<(), Error>;
+}
+
+// Picks a backend from the scheme of the database url
+pub async fn open_store(config: &Config) -> Result<Arc<dyn PawStore>, Error> {
+    if config.database_url.starts_with("memory:") {
+        return Ok(Arc::new(memory::MemoryStore::default()));
+    }
+
+    if config.database_url.starts_with("sqlite:") {
+        #[cfg(feature = "sqlite")]
+        return Ok(Arc::new(sqlite::SqliteStore::connect(&config.database_url, config.pool_size).await?));
+
+        #[cfg(not(feature = "sqlite"))]
+        return Err("DATABASE_URL points to SQLite but the bot was built without the sqlite feature".into());
+    }
+
+    Ok(Arc::new(postgres::PgStore::connect(&config.database_url, config.pool_size).await?))
+}
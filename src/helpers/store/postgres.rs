@@ -0,0 +1,1429 @@
+use async_trait::async_trait;
+use sqlx::{postgres::PgPoolOptions, prelude::FromRow, PgPool, Postgres, Transaction};
+use super::PawStore;
+use super::super::{migrations::{migration_status, run_migrations, MigrationStatus}, daily::parse_timezone, types::MyDuration};
+use super::super::structs::*;
+use chrono::{DateTime, Utc};
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct PgStore {
+    pool: PgPool
+}
+
+impl PgStore {
+    pub async fn connect(database_url: &str, pool_size: u32) -> Result<PgStore, Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(pool_size)
+            .connect(database_url)
+            .await?;
+        Ok(PgStore { pool })
+    }
+}
+
+#[derive(FromRow)]
+struct LedgerRow {
+    id: i64,
+    server_id: i64,
+    actor_id: i64,
+    source_id: Option<i64>,
+    target_id: Option<i64>,
+    amount: i64,
+    reason: String,
+    created_at: DateTime<Utc>
+}
+
+impl LedgerRow {
+    fn into_entry(self) -> Result<LedgerEntry, Error> {
+        Ok(LedgerEntry {
+            id: self.id,
+            server_id: self.server_id as u64,
+            actor_id: self.actor_id as u64,
+            source_id: self.source_id.map(|id| id as u64),
+            target_id: self.target_id.map(|id| id as u64),
+            amount: self.amount as u64,
+            reason: self.reason.parse()?,
+            created_at: self.created_at
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct LimitsRow {
+    user_id: i64,
+    #[sqlx(flatten)]
+    limits: UserLimits
+}
+
+#[derive(FromRow)]
+struct AuditRow {
+    id: i64,
+    server_id: i64,
+    actor_id: i64,
+    action: String,
+    target_id: Option<i64>,
+    amount: Option<i64>,
+    details: String,
+    created_at: DateTime<Utc>
+}
+
+impl AuditRow {
+    fn into_entry(self) -> Result<AuditEntry, Error> {
+        Ok(AuditEntry {
+            id: self.id,
+            server_id: self.server_id as u64,
+            actor_id: self.actor_id as u64,
+            action: self.action.parse()?,
+            target_id: self.target_id.map(|id| id as u64),
+            amount: self.amount.map(|amount| amount as u64),
+            details: self.details,
+            created_at: self.created_at
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct ServerSettingsRow {
+    steal_interval: MyDuration,
+    gamble_interval: MyDuration,
+    steal_chance: i32,
+    gamble_chance: Option<i32>,
+    gamble_critical_chance: i32,
+    gamble_partial_chance: i32,
+    steal_critical_chance: i32,
+    steal_partial_chance: i32,
+    steal_caught_chance: i32,
+    ranking_mode: String,
+    daily_reward: i32,
+    daily_streak_bonus: i32,
+    daily_streak_cap: i32,
+    daily_grace: MyDuration,
+    daily_reset: String,
+    timezone: String,
+    duration_style: String,
+    gamble_min_stake: i32,
+    gamble_max_stake: i32,
+    gamble_max_stake_percent: Option<i32>,
+    gamble_payout_percent: Option<i32>
+}
+
+#[derive(FromRow)]
+struct ShopItemRow {
+    id: i64,
+    server_id: i64,
+    name: String,
+    description: String,
+    price: i64,
+    effect: String,
+    strength: i32
+}
+
+impl ShopItemRow {
+    fn into_item(self) -> Result<ShopItem, Error> {
+        Ok(ShopItem {
+            id: self.id,
+            server_id: self.server_id as u64,
+            name: self.name,
+            description: self.description,
+            price: self.price as u64,
+            effect: self.effect.parse()?,
+            strength: self.strength
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct InventoryRow {
+    #[sqlx(flatten)]
+    item: ShopItemRow,
+    quantity: i64
+}
+
+impl InventoryRow {
+    fn into_inventory_item(self) -> Result<InventoryItem, Error> {
+        Ok(InventoryItem { item: self.item.into_item()?, quantity: self.quantity as u64 })
+    }
+}
+
+#[derive(FromRow)]
+struct ReminderRow {
+    server_id: i64,
+    user_id: i64,
+    action: String,
+    delivery: String,
+    channel_id: Option<i64>,
+    due_at: Option<DateTime<Utc>>
+}
+
+impl ReminderRow {
+    fn into_reminder(self) -> Result<Reminder, Error> {
+        Ok(Reminder {
+            server_id: self.server_id as u64,
+            user_id: self.user_id as u64,
+            action: self.action.parse()?,
+            delivery: self.delivery.parse()?,
+            channel_id: self.channel_id.map(|channel_id| channel_id as u64),
+            due_at: self.due_at
+        })
+    }
+}
+
+#[async_trait]
+impl PawStore for PgStore {
+    async fn migrate(&self) -> Result<usize, Error> {
+        let applied = run_migrations(&self.pool).await?;
+
+        let status = migration_status(&self.pool).await?;
+        if !status.is_up_to_date() {
+            return Err(format!("Database schema is at version {} but {} is required", status.current_version, status.latest_version).into());
+        }
+
+        Ok(applied)
+    }
+
+    async fn migration_status(&self) -> Result<MigrationStatus, Error> {
+        migration_status(&self.pool).await
+    }
+
+    async fn get_paw_count(&self, user_id: u64, server_id: u64) -> Result<u64, Error> {
+        let result = sqlx::query_as::<_,(i64,)>("SELECT count FROM \"paw-bot\".\"paw_count\" WHERE user_id = $1 AND server_id = $2")
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .fetch_one(&self.pool)
+            .await;
+    
+        match result {
+            Ok((count,)) => Ok(count as u64),
+            Err(sqlx::Error::RowNotFound) => Ok(0), // Handle RowNotFound specifically
+            Err(e) => Err(Box::new(e)), // Propagate other errors
+        }
+    }
+
+    // Moves paws between two users (or a user and the house) in a single transaction.
+    // Both balances are locked for the duration, so the source can never be overdrawn
+    // by concurrent commands, and the movement is appended to the ledger.
+    async fn transfer_paws(&self, transfer: &Transfer) -> Result<TransferResult, Error> {
+        let mut transaction = self.pool.begin().await?;
+        let result = transfer_in(&mut transaction, transfer).await?;
+
+        // Dropping the transaction rolls back the placeholder rows
+        if let TransferResult::Completed { .. } = result {
+            transaction.commit().await?;
+        }
+
+        Ok(result)
+    }
+
+    async fn get_ledger(&self, server_id: u64, filter: LedgerFilter, offset: u64, limit: u64) -> Result<Vec<LedgerEntry>, Error> {
+        let rows = sqlx::query_as::<_,LedgerRow>(r#"
+            SELECT id, server_id, actor_id, source_id, target_id, amount, reason, created_at
+            FROM "paw-bot"."paw_ledger"
+            WHERE server_id = $1 AND ($2::int8 IS NULL OR source_id = $2 OR target_id = $2) AND ($3::text IS NULL OR reason = $3)
+            ORDER BY id DESC
+            LIMIT $4 OFFSET $5
+        "#)
+            .bind(server_id as i64)
+            .bind(filter.user_id.map(|id| id as i64))
+            .bind(filter.reason.map(|reason| reason.as_str()))
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(LedgerRow::into_entry).collect()
+    }
+
+    async fn count_ledger(&self, server_id: u64, filter: LedgerFilter) -> Result<u64, Error> {
+        let count = sqlx::query_scalar::<_,i64>(r#"
+            SELECT COUNT(*) FROM "paw-bot"."paw_ledger"
+            WHERE server_id = $1 AND ($2::int8 IS NULL OR source_id = $2 OR target_id = $2) AND ($3::text IS NULL OR reason = $3)
+        "#)
+            .bind(server_id as i64)
+            .bind(filter.user_id.map(|id| id as i64))
+            .bind(filter.reason.map(|reason| reason.as_str()))
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count as u64)
+    }
+
+    async fn set_paw_count(&self, user_id: u64, server_id: u64, actor_id: u64, count: u64) -> Result<u64, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let previous = sqlx::query_scalar::<_,i64>(r#"
+            SELECT count FROM "paw-bot"."paw_count" WHERE user_id = $1 AND server_id = $2 FOR UPDATE
+        "#)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .unwrap_or(0) as u64;
+
+        if let Some(transfer) = Transfer::set_balance(server_id, user_id, actor_id, previous, count, LedgerReason::AdminSet) {
+            let TransferResult::Completed { .. } = transfer_in(&mut transaction, &transfer).await? else {
+                return Err("The balance changed while it was being set".into());
+            };
+        }
+        transaction.commit().await?;
+
+        Ok(previous)
+    }
+
+    async fn reset_economy(&self, server_id: u64, actor_id: u64, now: DateTime<Utc>) -> Result<EconomyReset, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let snapshot_id = sqlx::query_scalar::<_,i64>(r#"
+            INSERT INTO "paw-bot"."economy_snapshots" (server_id, actor_id, created_at)
+            VALUES ($1, $2, $3)
+            RETURNING id
+        "#)
+            .bind(server_id as i64)
+            .bind(actor_id as i64)
+            .bind(now)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        // Nothing can move while the balances are copied and taken back
+        let balances = sqlx::query_as::<_,(i64, i64)>(r#"
+            SELECT user_id, count FROM "paw-bot"."paw_count" WHERE server_id = $1 AND count > 0 FOR UPDATE
+        "#)
+            .bind(server_id as i64)
+            .fetch_all(&mut *transaction)
+            .await?;
+
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."economy_snapshot_balances" (snapshot_id, user_id, count)
+            SELECT $1, user_id, count FROM "paw-bot"."paw_count" WHERE server_id = $2 AND count > 0
+        "#)
+            .bind(snapshot_id)
+            .bind(server_id as i64)
+            .execute(&mut *transaction)
+            .await?;
+
+        // Every balance goes back to the house through the ledger, like any other transfer
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."paw_ledger" (server_id, actor_id, source_id, target_id, amount, reason, created_at)
+            SELECT server_id, $2, user_id, NULL, count, $3, $4 FROM "paw-bot"."paw_count" WHERE server_id = $1 AND count > 0
+        "#)
+            .bind(server_id as i64)
+            .bind(actor_id as i64)
+            .bind(LedgerReason::AdminReset.as_str())
+            .bind(now)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query(r#"DELETE FROM "paw-bot"."paw_count" WHERE server_id = $1"#)
+            .bind(server_id as i64)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(EconomyReset {
+            snapshot_id,
+            farmers: balances.len() as u64,
+            paws: balances.iter().fold(0, |paws: u64, (_, count)| paws.saturating_add(*count as u64))
+        })
+    }
+
+    async fn export_economy(&self, server_id: u64) -> Result<EconomyData, Error> {
+        let balances = sqlx::query_as::<_,(i64, i64)>(r#"
+            SELECT user_id, count FROM "paw-bot"."paw_count" WHERE server_id = $1
+            ORDER BY user_id
+        "#)
+            .bind(server_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let limits = sqlx::query_as::<_,LimitsRow>(r#"
+            SELECT user_id, last_steal, last_daily, last_gamble, daily_streak, best_daily_streak
+            FROM "paw-bot"."user_limits" WHERE server_id = $1
+            ORDER BY user_id
+        "#)
+            .bind(server_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(EconomyData {
+            balances: balances.into_iter().map(|(user_id, count)| (user_id as u64, count as u64)).collect(),
+            limits: limits.into_iter().map(|row| (row.user_id as u64, row.limits)).collect(),
+            settings: self.get_server_settings(server_id).await?
+        })
+    }
+
+    async fn import_economy(&self, server_id: u64, actor_id: u64, data: &EconomyData, mode: ImportMode) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let current = sqlx::query_as::<_,(i64, i64)>(r#"
+            SELECT user_id, count FROM "paw-bot"."paw_count" WHERE server_id = $1 FOR UPDATE
+        "#)
+            .bind(server_id as i64)
+            .fetch_all(&mut *transaction)
+            .await?;
+        let current: Vec<(u64, u64)> = current.into_iter().map(|(user_id, count)| (user_id as u64, count as u64)).collect();
+
+        // Balances move through the ledger like any other change
+        for transfer in data.import_transfers(server_id, actor_id, &current, mode) {
+            let TransferResult::Completed { .. } = transfer_in(&mut transaction, &transfer).await? else {
+                return Err("A balance changed while it was being imported".into());
+            };
+        }
+
+        if mode == ImportMode::Replace {
+            // Everyone dropped has nothing left by now
+            sqlx::query(r#"DELETE FROM "paw-bot"."paw_count" WHERE server_id = $1 AND count = 0"#)
+                .bind(server_id as i64)
+                .execute(&mut *transaction)
+                .await?;
+
+            for table in ["user_limits", "server_settings"] {
+                sqlx::query(&format!("DELETE FROM \"paw-bot\".\"{}\" WHERE server_id = $1", table))
+                    .bind(server_id as i64)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+        }
+
+        // Limits go in as one statement, each column as an array
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."user_limits" (user_id, server_id, last_steal, last_daily, last_gamble, daily_streak, best_daily_streak)
+            SELECT user_id, $1, last_steal, last_daily, last_gamble, daily_streak, best_daily_streak
+            FROM UNNEST($2::int8[], $3::timestamptz[], $4::timestamptz[], $5::timestamptz[], $6::int4[], $7::int4[])
+                AS limits (user_id, last_steal, last_daily, last_gamble, daily_streak, best_daily_streak)
+            ON CONFLICT (user_id, server_id)
+            DO UPDATE SET last_steal = EXCLUDED.last_steal, last_daily = EXCLUDED.last_daily, last_gamble = EXCLUDED.last_gamble,
+                daily_streak = EXCLUDED.daily_streak, best_daily_streak = EXCLUDED.best_daily_streak;
+        "#)
+            .bind(server_id as i64)
+            .bind(data.limits.iter().map(|(user_id, _)| *user_id as i64).collect::<Vec<i64>>())
+            .bind(data.limits.iter().map(|(_, limits)| limits.last_steal).collect::<Vec<Option<DateTime<Utc>>>>())
+            .bind(data.limits.iter().map(|(_, limits)| limits.last_daily).collect::<Vec<Option<DateTime<Utc>>>>())
+            .bind(data.limits.iter().map(|(_, limits)| limits.last_gamble).collect::<Vec<Option<DateTime<Utc>>>>())
+            .bind(data.limits.iter().map(|(_, limits)| limits.daily_streak).collect::<Vec<i32>>())
+            .bind(data.limits.iter().map(|(_, limits)| limits.best_daily_streak).collect::<Vec<i32>>())
+            .execute(&mut *transaction)
+            .await?;
+
+        if let Some(settings) = &data.settings {
+            upsert_settings_in(&mut transaction, server_id, settings).await?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn add_audit_entry(&self, entry: &AuditEntry) -> Result<(), Error> {
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."admin_audit_log" (server_id, actor_id, action, target_id, amount, details, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#)
+            .bind(entry.server_id as i64)
+            .bind(entry.actor_id as i64)
+            .bind(entry.action.as_str())
+            .bind(entry.target_id.map(|id| id as i64))
+            .bind(entry.amount.map(|amount| amount as i64))
+            .bind(&entry.details)
+            .bind(entry.created_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_audit_log(&self, server_id: u64, limit: u64) -> Result<Vec<AuditEntry>, Error> {
+        let rows = sqlx::query_as::<_,AuditRow>(r#"
+            SELECT id, server_id, actor_id, action, target_id, amount, details, created_at
+            FROM "paw-bot"."admin_audit_log" WHERE server_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+        "#)
+            .bind(server_id as i64)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(AuditRow::into_entry).collect()
+    }
+
+    async fn get_last_used(&self, user_id: u64, server_id: u64, cooldown: Cooldown) -> Result<Option<DateTime<Utc>>, Error> {
+        // The column is NULL when the row was created by another action
+        let query = format!("SELECT {} FROM \"paw-bot\".\"user_limits\" WHERE user_id = $1 AND server_id = $2", cooldown.column());
+
+        let time = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(&query)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(time.flatten())
+    }
+
+    async fn claim_cooldown(&self, user_id: u64, server_id: u64, cooldown: Cooldown, previous: Option<DateTime<Utc>>, time: DateTime<Utc>) -> Result<bool, Error> {
+        let query = format!(r#"
+            INSERT INTO "paw-bot"."user_limits" (user_id, server_id, {column})
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, server_id)
+            DO UPDATE SET {column} = $3
+            WHERE "user_limits".{column} IS NOT DISTINCT FROM $4;
+        "#, column = cooldown.column());
+
+        let claimed = sqlx::query(&query)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .bind(time)
+            .bind(previous)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(claimed.rows_affected() > 0)
+    }
+
+    async fn get_daily_streak(&self, user_id: u64, server_id: u64) -> Result<DailyStreak, Error> {
+        let row = sqlx::query_as::<_,(Option<DateTime<Utc>>, i32, i32)>(r#"
+            SELECT last_daily, daily_streak, best_daily_streak FROM "paw-bot"."user_limits"
+            WHERE user_id = $1 AND server_id = $2
+        "#)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some((last_claimed, current, best)) = row else {
+            return Ok(DailyStreak::default());
+        };
+
+        Ok(DailyStreak { last_claimed, current: current as u32, best: best as u32 })
+    }
+
+    async fn claim_daily(&self, claim: &DailyClaim) -> Result<Option<u64>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // The row is only written if nobody claimed since the streak was read
+        let claimed = sqlx::query(r#"
+            INSERT INTO "paw-bot"."user_limits" (user_id, server_id, last_daily, daily_streak, best_daily_streak)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id, server_id)
+            DO UPDATE SET last_daily = $3, daily_streak = $4, best_daily_streak = $5
+            WHERE "user_limits".last_daily IS NOT DISTINCT FROM $6;
+        "#)
+            .bind(claim.user_id as i64)
+            .bind(claim.server_id as i64)
+            .bind(claim.claimed_at)
+            .bind(claim.streak as i32)
+            .bind(claim.best as i32)
+            .bind(claim.previous)
+            .execute(&mut *transaction)
+            .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let TransferResult::Completed { target_count, .. } = transfer_in(&mut transaction, &claim.transfer()).await? else {
+            return Err("The house can't run out of paws".into());
+        };
+        transaction.commit().await?;
+
+        Ok(target_count)
+    }
+
+    async fn place_stake(&self, stake: &GameStake) -> Result<Option<TransferResult>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // The row is only written if no game started since the cooldown was read
+        let claimed = sqlx::query(r#"
+            INSERT INTO "paw-bot"."user_limits" (user_id, server_id, last_gamble)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, server_id)
+            DO UPDATE SET last_gamble = $3
+            WHERE "user_limits".last_gamble IS NOT DISTINCT FROM $4;
+        "#)
+            .bind(stake.user_id as i64)
+            .bind(stake.server_id as i64)
+            .bind(stake.placed_at)
+            .bind(stake.previous)
+            .execute(&mut *transaction)
+            .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        // Players who can't cover the stake keep their cooldown
+        let held = transfer_in(&mut transaction, &stake.transfer()).await?;
+        if let TransferResult::Completed { .. } = held {
+            sqlx::query(r#"
+                INSERT INTO "paw-bot"."open_games" (server_id, user_id, stake, placed_at)
+                VALUES ($1, $2, $3, $4)
+            "#)
+                .bind(stake.server_id as i64)
+                .bind(stake.user_id as i64)
+                .bind(stake.stake as i64)
+                .bind(stake.placed_at)
+                .execute(&mut *transaction)
+                .await?;
+            transaction.commit().await?;
+        }
+
+        Ok(Some(held))
+    }
+
+    async fn settle_game(&self, result: &GameResult) -> Result<Option<u64>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let closed = sqlx::query(r#"
+            DELETE FROM "paw-bot"."open_games" WHERE server_id = $1 AND user_id = $2 AND placed_at = $3
+        "#)
+            .bind(result.server_id as i64)
+            .bind(result.user_id as i64)
+            .bind(result.played_at)
+            .execute(&mut *transaction)
+            .await?;
+        if closed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let balance = match result.transfer() {
+            Some(transfer) => {
+                let TransferResult::Completed { target_count, .. } = transfer_in(&mut transaction, &transfer).await? else {
+                    return Err("The house can't run out of paws".into());
+                };
+                target_count.unwrap_or(0)
+            }
+            None => {
+                let count = sqlx::query_scalar::<_,i64>(r#"
+                    SELECT count FROM "paw-bot"."paw_count" WHERE user_id = $1 AND server_id = $2
+                "#)
+                    .bind(result.user_id as i64)
+                    .bind(result.server_id as i64)
+                    .fetch_optional(&mut *transaction)
+                    .await?;
+
+                count.unwrap_or(0) as u64
+            }
+        };
+
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."game_results" (server_id, user_id, game, stake, payout, played_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        "#)
+            .bind(result.server_id as i64)
+            .bind(result.user_id as i64)
+            .bind(result.game.as_str())
+            .bind(result.stake as i64)
+            .bind(result.payout)
+            .bind(result.played_at)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(Some(balance))
+    }
+
+    async fn refund_game(&self, game: &OpenGame) -> Result<Option<u64>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let closed = sqlx::query(r#"
+            DELETE FROM "paw-bot"."open_games" WHERE server_id = $1 AND user_id = $2 AND placed_at = $3
+        "#)
+            .bind(game.server_id as i64)
+            .bind(game.user_id as i64)
+            .bind(game.placed_at)
+            .execute(&mut *transaction)
+            .await?;
+        if closed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let TransferResult::Completed { target_count, .. } = transfer_in(&mut transaction, &game.refund()).await? else {
+            return Err("The house can't run out of paws".into());
+        };
+        transaction.commit().await?;
+
+        Ok(target_count)
+    }
+
+    async fn get_open_games(&self, placed_before: DateTime<Utc>) -> Result<Vec<OpenGame>, Error> {
+        let rows = sqlx::query_as::<_,(i64, i64, i64, DateTime<Utc>)>(r#"
+            SELECT server_id, user_id, stake, placed_at FROM "paw-bot"."open_games" WHERE placed_at < $1
+        "#)
+            .bind(placed_before)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|(server_id, user_id, stake, placed_at)| OpenGame {
+                server_id: server_id as u64,
+                user_id: user_id as u64,
+                stake: stake as u64,
+                placed_at
+            })
+            .collect())
+    }
+
+    async fn get_game_stats(&self, server_id: u64) -> Result<Vec<GameStats>, Error> {
+        let rows = sqlx::query_as::<_,(String, i64, i64, i64)>(r#"
+            SELECT game, COUNT(*), SUM(stake)::int8, SUM(payout)::int8
+            FROM "paw-bot"."game_results" WHERE server_id = $1
+            GROUP BY game
+            ORDER BY game
+        "#)
+            .bind(server_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(game, played, staked, payout)| Ok(GameStats {
+                game: game.parse()?,
+                played: played as u64,
+                staked: staked as u64,
+                payout
+            }))
+            .collect()
+    }
+
+    async fn open_duel(&self, duel: &Duel) -> Result<Option<i64>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        if let TransferResult::InsufficientFunds { .. } = transfer_in(&mut transaction, &duel.escrow()).await? {
+            return Ok(None);
+        }
+
+        let id = sqlx::query_scalar::<_,i64>(r#"
+            INSERT INTO "paw-bot"."duels" (server_id, challenger_id, target_id, stake, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+        "#)
+            .bind(duel.server_id as i64)
+            .bind(duel.challenger_id as i64)
+            .bind(duel.target_id as i64)
+            .bind(duel.stake as i64)
+            .bind(duel.expires_at)
+            .fetch_one(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+
+        Ok(Some(id))
+    }
+
+    async fn accept_duel(&self, duel: &Duel, winner_id: u64) -> Result<DuelResult, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Whoever deletes the row gets to settle it, a refund may have beaten us to it
+        let closed = sqlx::query(r#"DELETE FROM "paw-bot"."duels" WHERE id = $1"#)
+            .bind(duel.id)
+            .execute(&mut *transaction)
+            .await?;
+        if closed.rows_affected() == 0 {
+            return Ok(DuelResult::Closed);
+        }
+
+        if let TransferResult::InsufficientFunds { available } = transfer_in(&mut transaction, &duel.accept()).await? {
+            return Ok(DuelResult::InsufficientFunds { available });
+        }
+
+        let TransferResult::Completed { target_count: Some(winner_count), .. } = transfer_in(&mut transaction, &duel.payout(winner_id)).await? else {
+            return Err("The house can't run out of paws".into());
+        };
+
+        let loser_id = if winner_id == duel.challenger_id {duel.target_id} else {duel.challenger_id};
+        let loser_count = sqlx::query_scalar::<_,i64>(r#"SELECT count FROM "paw-bot"."paw_count" WHERE user_id = $1 AND server_id = $2"#)
+            .bind(loser_id as i64)
+            .bind(duel.server_id as i64)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .unwrap_or(0);
+        transaction.commit().await?;
+
+        Ok(DuelResult::Fought { winner_count, loser_count: loser_count as u64 })
+    }
+
+    async fn cancel_duel(&self, duel: &Duel) -> Result<Option<u64>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let closed = sqlx::query(r#"DELETE FROM "paw-bot"."duels" WHERE id = $1"#)
+            .bind(duel.id)
+            .execute(&mut *transaction)
+            .await?;
+        if closed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let TransferResult::Completed { target_count, .. } = transfer_in(&mut transaction, &duel.refund()).await? else {
+            return Err("The house can't run out of paws".into());
+        };
+        transaction.commit().await?;
+
+        Ok(target_count)
+    }
+
+    async fn get_expired_duels(&self, now: DateTime<Utc>) -> Result<Vec<Duel>, Error> {
+        let rows = sqlx::query_as::<_,(i64, i64, i64, i64, i64, DateTime<Utc>)>(r#"
+            SELECT id, server_id, challenger_id, target_id, stake, expires_at FROM "paw-bot"."duels" WHERE expires_at <= $1
+        "#)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|(id, server_id, challenger_id, target_id, stake, expires_at)| Duel {
+                id,
+                server_id: server_id as u64,
+                challenger_id: challenger_id as u64,
+                target_id: target_id as u64,
+                stake: stake as u64,
+                expires_at
+            })
+            .collect())
+    }
+
+    async fn get_server_settings(&self, server_id: u64) -> Result<Option<ServerSettings>, Error> {
+        let row = sqlx::query_as::<_,ServerSettingsRow>(r#"
+            SELECT steal_interval, gamble_interval, steal_chance, gamble_chance,
+                gamble_critical_chance, gamble_partial_chance,
+                steal_critical_chance, steal_partial_chance, steal_caught_chance, ranking_mode,
+                daily_reward, daily_streak_bonus, daily_streak_cap, daily_grace, daily_reset, timezone,
+                duration_style, gamble_min_stake, gamble_max_stake, gamble_max_stake_percent, gamble_payout_percent
+            FROM "paw-bot"."server_settings" WHERE server_id = $1
+        "#)
+            .bind(server_id as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        // gamble_chance is nullable, fall back to the default for it alone
+        Ok(Some(ServerSettings {
+            steal_interval: row.steal_interval,
+            gamble_interval: row.gamble_interval,
+            steal_chance: row.steal_chance,
+            gamble_chance: row.gamble_chance.unwrap_or(ServerSettings::default().gamble_chance),
+            gamble_critical_chance: row.gamble_critical_chance,
+            gamble_partial_chance: row.gamble_partial_chance,
+            steal_critical_chance: row.steal_critical_chance,
+            steal_partial_chance: row.steal_partial_chance,
+            steal_caught_chance: row.steal_caught_chance,
+            ranking_mode: row.ranking_mode.parse()?,
+            daily_reward: row.daily_reward,
+            daily_streak_bonus: row.daily_streak_bonus,
+            daily_streak_cap: row.daily_streak_cap,
+            daily_grace: row.daily_grace,
+            daily_reset: row.daily_reset.parse()?,
+            timezone: parse_timezone(&row.timezone)?,
+            duration_style: row.duration_style.parse()?,
+            gamble_min_stake: row.gamble_min_stake,
+            gamble_max_stake: row.gamble_max_stake,
+            gamble_max_stake_percent: row.gamble_max_stake_percent,
+            gamble_payout_percent: row.gamble_payout_percent
+        }))
+    }
+
+    async fn upsert_server_settings(&self, server_id: u64, settings: &ServerSettings) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+        upsert_settings_in(&mut transaction, server_id, settings).await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    // Returns true if the server had settings to remove
+    async fn delete_server_settings(&self, server_id: u64) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM \"paw-bot\".\"server_settings\" WHERE server_id = $1")
+            .bind(server_id as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_leaderboard(&self, server_id: u64, offset: u64, limit: u64) -> Result<Vec<PawCount>, Error> {
+        let leaderboard = sqlx::query_as::<_,PawCount>(r#"
+            SELECT count, user_id FROM "paw-bot"."paw_count"
+            WHERE server_id = $1
+            ORDER BY count DESC, user_id
+            LIMIT $2 OFFSET $3
+        "#)
+            .bind(server_id as i64)
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(leaderboard)
+    }
+
+    async fn get_leaderboard_totals(&self, server_id: u64) -> Result<(u64, u64), Error> {
+        // SUM is NULL on a server without farmers
+        let (farmers, total_paws) = sqlx::query_as::<_,(i64,i64)>("SELECT COUNT(*), COALESCE(SUM(count), 0)::BIGINT FROM \"paw-bot\".\"paw_count\" WHERE server_id = $1")
+            .bind(server_id as i64)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((farmers as u64, total_paws as u64))
+    }
+
+    async fn count_farmers_above(&self, server_id: u64, count: u64, distinct: bool) -> Result<u64, Error> {
+        let query = if distinct {
+            "SELECT COUNT(DISTINCT count) FROM \"paw-bot\".\"paw_count\" WHERE server_id = $1 AND count > $2"
+        } else {
+            "SELECT COUNT(*) FROM \"paw-bot\".\"paw_count\" WHERE server_id = $1 AND count > $2"
+        };
+
+        let above = sqlx::query_scalar::<_,i64>(query)
+            .bind(server_id as i64)
+            .bind(count as i64)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(above as u64)
+    }
+
+    async fn count_tied_before(&self, server_id: u64, count: u64, user_id: u64) -> Result<u64, Error> {
+        let tied = sqlx::query_scalar::<_,i64>("SELECT COUNT(*) FROM \"paw-bot\".\"paw_count\" WHERE server_id = $1 AND count = $2 AND user_id < $3")
+            .bind(server_id as i64)
+            .bind(count as i64)
+            .bind(user_id as i64)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(tied as u64)
+    }
+
+    async fn get_user_profiles(&self, user_ids: &[u64]) -> Result<Vec<UserProfile>, Error> {
+        let user_ids: Vec<i64> = user_ids.iter().map(|user_id| *user_id as i64).collect();
+
+        let profiles = sqlx::query_as::<_,UserProfile>("SELECT user_id, display_name, avatar_url, updated_at FROM \"paw-bot\".\"user_profiles\" WHERE user_id = ANY($1)")
+            .bind(user_ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(profiles)
+    }
+
+    async fn upsert_user_profiles(&self, profiles: &[UserProfile]) -> Result<(), Error> {
+        if profiles.is_empty() {
+            return Ok(());
+        }
+
+        // One statement for the whole batch, each column goes in as an array
+        let query = r#"
+            INSERT INTO "paw-bot"."user_profiles" (user_id, display_name, avatar_url, updated_at)
+            SELECT * FROM UNNEST($1::bigint[], $2::text[], $3::text[], $4::timestamptz[])
+            ON CONFLICT (user_id)
+            DO UPDATE SET display_name = EXCLUDED.display_name, avatar_url = EXCLUDED.avatar_url, updated_at = EXCLUDED.updated_at;
+        "#;
+
+        sqlx::query(query)
+            .bind(profiles.iter().map(|profile| profile.user_id).collect::<Vec<i64>>())
+            .bind(profiles.iter().map(|profile| profile.display_name.clone()).collect::<Vec<String>>())
+            .bind(profiles.iter().map(|profile| profile.avatar_url.clone()).collect::<Vec<Option<String>>>())
+            .bind(profiles.iter().map(|profile| profile.updated_at).collect::<Vec<DateTime<Utc>>>())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_shop_items(&self, server_id: u64) -> Result<Vec<ShopItem>, Error> {
+        let rows = sqlx::query_as::<_,ShopItemRow>(r#"
+            SELECT id, server_id, name, description, price, effect, strength
+            FROM "paw-bot"."shop_items" WHERE server_id = $1
+            ORDER BY price, name
+        "#)
+            .bind(server_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(ShopItemRow::into_item).collect()
+    }
+
+    async fn upsert_shop_item(&self, item: &ShopItem) -> Result<ShopItem, Error> {
+        let row = sqlx::query_as::<_,ShopItemRow>(r#"
+            INSERT INTO "paw-bot"."shop_items" (server_id, name, description, price, effect, strength)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (server_id, name)
+            DO UPDATE SET description = EXCLUDED.description, price = EXCLUDED.price, effect = EXCLUDED.effect, strength = EXCLUDED.strength
+            RETURNING id, server_id, name, description, price, effect, strength;
+        "#)
+            .bind(item.server_id as i64)
+            .bind(&item.name)
+            .bind(&item.description)
+            .bind(item.price as i64)
+            .bind(item.effect.as_str())
+            .bind(item.strength)
+            .fetch_one(&self.pool)
+            .await?;
+
+        row.into_item()
+    }
+
+    async fn delete_shop_item(&self, server_id: u64, name: &str) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM \"paw-bot\".\"shop_items\" WHERE server_id = $1 AND name = $2")
+            .bind(server_id as i64)
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn buy_item(&self, user_id: u64, item: &ShopItem, quantity: u64) -> Result<TransferResult, Error> {
+        let amount = item.price.checked_mul(quantity).ok_or("Purchase is too expensive")?;
+        let mut transaction = self.pool.begin().await?;
+
+        let result = transfer_in(&mut transaction, &Transfer {
+            server_id: item.server_id,
+            actor_id: user_id,
+            source_id: Some(user_id),
+            target_id: None,
+            amount,
+            reason: LedgerReason::ShopPurchase
+        }).await?;
+
+        if let TransferResult::InsufficientFunds { .. } = result {
+            return Ok(result);
+        }
+
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."user_items" (user_id, server_id, item_id, quantity)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, server_id, item_id)
+            DO UPDATE SET quantity = "paw-bot"."user_items".quantity + EXCLUDED.quantity;
+        "#)
+            .bind(user_id as i64)
+            .bind(item.server_id as i64)
+            .bind(item.id)
+            .bind(quantity as i64)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn get_inventory(&self, user_id: u64, server_id: u64) -> Result<Vec<InventoryItem>, Error> {
+        let rows = sqlx::query_as::<_,InventoryRow>(r#"
+            SELECT i.id, i.server_id, i.name, i.description, i.price, i.effect, i.strength, u.quantity
+            FROM "paw-bot"."user_items" u
+            JOIN "paw-bot"."shop_items" i ON i.id = u.item_id
+            WHERE u.user_id = $1 AND u.server_id = $2
+            ORDER BY i.price, i.name
+        "#)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(InventoryRow::into_inventory_item).collect()
+    }
+
+    async fn consume_item(&self, user_id: u64, server_id: u64, effect: ItemEffect) -> Result<Option<ShopItem>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Locked so two commands can't use the same item
+        let row = sqlx::query_as::<_,InventoryRow>(r#"
+            SELECT i.id, i.server_id, i.name, i.description, i.price, i.effect, i.strength, u.quantity
+            FROM "paw-bot"."user_items" u
+            JOIN "paw-bot"."shop_items" i ON i.id = u.item_id
+            WHERE u.user_id = $1 AND u.server_id = $2 AND i.effect = $3
+            ORDER BY i.strength DESC, i.id
+            LIMIT 1
+            FOR UPDATE OF u;
+        "#)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .bind(effect.as_str())
+            .fetch_optional(&mut *transaction)
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let query = if row.quantity > 1 {
+            "UPDATE \"paw-bot\".\"user_items\" SET quantity = quantity - 1 WHERE user_id = $1 AND server_id = $2 AND item_id = $3"
+        } else {
+            "DELETE FROM \"paw-bot\".\"user_items\" WHERE user_id = $1 AND server_id = $2 AND item_id = $3"
+        };
+
+        sqlx::query(query)
+            .bind(user_id as i64)
+            .bind(server_id as i64)
+            .bind(row.item.id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(Some(row.item.into_item()?))
+    }
+
+    async fn get_role_listings(&self, server_id: u64) -> Result<Vec<RoleListing>, Error> {
+        let rows = sqlx::query_as::<_,(i64, i64, Option<i32>)>("SELECT role_id, price, duration_days FROM \"paw-bot\".\"role_listings\" WHERE server_id = $1 ORDER BY price, role_id")
+            .bind(server_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|(role_id, price, duration_days)| RoleListing { server_id, role_id: role_id as u64, price: price as u64, duration_days })
+            .collect())
+    }
+
+    async fn upsert_role_listing(&self, listing: &RoleListing) -> Result<(), Error> {
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."role_listings" (server_id, role_id, price, duration_days)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (server_id, role_id)
+            DO UPDATE SET price = EXCLUDED.price, duration_days = EXCLUDED.duration_days;
+        "#)
+            .bind(listing.server_id as i64)
+            .bind(listing.role_id as i64)
+            .bind(listing.price as i64)
+            .bind(listing.duration_days)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_role_listing(&self, server_id: u64, role_id: u64) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM \"paw-bot\".\"role_listings\" WHERE server_id = $1 AND role_id = $2")
+            .bind(server_id as i64)
+            .bind(role_id as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn buy_role(&self, user_id: u64, listing: &RoleListing, now: DateTime<Utc>) -> Result<TransferResult, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let result = transfer_in(&mut transaction, &Transfer {
+            server_id: listing.server_id,
+            actor_id: user_id,
+            source_id: Some(user_id),
+            target_id: None,
+            amount: listing.price,
+            reason: LedgerReason::RolePurchase
+        }).await?;
+
+        if let TransferResult::InsufficientFunds { .. } = result {
+            return Ok(result);
+        }
+
+        if listing.duration_days.is_some() {
+            let current = sqlx::query_scalar::<_,DateTime<Utc>>(r#"
+                SELECT expires_at FROM "paw-bot"."role_rentals"
+                WHERE server_id = $1 AND user_id = $2 AND role_id = $3
+                FOR UPDATE;
+            "#)
+                .bind(listing.server_id as i64)
+                .bind(user_id as i64)
+                .bind(listing.role_id as i64)
+                .fetch_optional(&mut *transaction)
+                .await?;
+
+            sqlx::query(r#"
+                INSERT INTO "paw-bot"."role_rentals" (server_id, user_id, role_id, expires_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (server_id, user_id, role_id)
+                DO UPDATE SET expires_at = EXCLUDED.expires_at;
+            "#)
+                .bind(listing.server_id as i64)
+                .bind(user_id as i64)
+                .bind(listing.role_id as i64)
+                .bind(listing.rental_end(current, now))
+                .execute(&mut *transaction)
+                .await?;
+        } else {
+            // Held for good now, so the expiry job mustn't take it away
+            sqlx::query(r#"
+                DELETE FROM "paw-bot"."role_rentals" WHERE server_id = $1 AND user_id = $2 AND role_id = $3;
+            "#)
+                .bind(listing.server_id as i64)
+                .bind(user_id as i64)
+                .bind(listing.role_id as i64)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn get_role_rentals(&self, server_id: u64, user_id: u64) -> Result<Vec<RoleRental>, Error> {
+        let rows = sqlx::query_as::<_,(i64, DateTime<Utc>)>("SELECT role_id, expires_at FROM \"paw-bot\".\"role_rentals\" WHERE server_id = $1 AND user_id = $2")
+            .bind(server_id as i64)
+            .bind(user_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|(role_id, expires_at)| RoleRental { server_id, user_id, role_id: role_id as u64, expires_at })
+            .collect())
+    }
+
+    async fn get_expired_role_rentals(&self, now: DateTime<Utc>) -> Result<Vec<RoleRental>, Error> {
+        let rows = sqlx::query_as::<_,(i64, i64, i64, DateTime<Utc>)>("SELECT server_id, user_id, role_id, expires_at FROM \"paw-bot\".\"role_rentals\" WHERE expires_at <= $1")
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|(server_id, user_id, role_id, expires_at)| RoleRental { server_id: server_id as u64, user_id: user_id as u64, role_id: role_id as u64, expires_at })
+            .collect())
+    }
+
+    async fn delete_role_rental(&self, rental: &RoleRental) -> Result<(), Error> {
+        // Leave it alone if it was extended since it was read
+        sqlx::query("DELETE FROM \"paw-bot\".\"role_rentals\" WHERE server_id = $1 AND user_id = $2 AND role_id = $3 AND expires_at <= $4")
+            .bind(rental.server_id as i64)
+            .bind(rental.user_id as i64)
+            .bind(rental.role_id as i64)
+            .bind(rental.expires_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_reminders(&self, server_id: u64, user_id: u64) -> Result<Vec<Reminder>, Error> {
+        let rows = sqlx::query_as::<_,ReminderRow>(r#"
+            SELECT server_id, user_id, action, delivery, channel_id, due_at FROM "paw-bot"."reminders"
+            WHERE server_id = $1 AND user_id = $2
+            ORDER BY action
+        "#)
+            .bind(server_id as i64)
+            .bind(user_id as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(ReminderRow::into_reminder).collect()
+    }
+
+    async fn upsert_reminder(&self, reminder: &Reminder) -> Result<(), Error> {
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."reminders" (server_id, user_id, action, delivery, channel_id, due_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (server_id, user_id, action)
+            DO UPDATE SET delivery = $4, channel_id = $5, due_at = $6
+        "#)
+            .bind(reminder.server_id as i64)
+            .bind(reminder.user_id as i64)
+            .bind(reminder.action.as_str())
+            .bind(reminder.delivery.as_str())
+            .bind(reminder.channel_id.map(|channel_id| channel_id as i64))
+            .bind(reminder.due_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_reminder(&self, server_id: u64, user_id: u64, action: ReminderAction) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM \"paw-bot\".\"reminders\" WHERE server_id = $1 AND user_id = $2 AND action = $3")
+            .bind(server_id as i64)
+            .bind(user_id as i64)
+            .bind(action.as_str())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn schedule_reminder(&self, server_id: u64, user_id: u64, action: ReminderAction, due_at: DateTime<Utc>) -> Result<(), Error> {
+        sqlx::query("UPDATE \"paw-bot\".\"reminders\" SET due_at = $4 WHERE server_id = $1 AND user_id = $2 AND action = $3")
+            .bind(server_id as i64)
+            .bind(user_id as i64)
+            .bind(action.as_str())
+            .bind(due_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>, Error> {
+        let rows = sqlx::query_as::<_,ReminderRow>(r#"
+            SELECT server_id, user_id, action, delivery, channel_id, due_at FROM "paw-bot"."reminders"
+            WHERE due_at <= $1
+        "#)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(ReminderRow::into_reminder).collect()
+    }
+
+    async fn clear_reminder(&self, reminder: &Reminder) -> Result<(), Error> {
+        // Leave it alone if it was rescheduled since it was read
+        sqlx::query("UPDATE \"paw-bot\".\"reminders\" SET due_at = NULL WHERE server_id = $1 AND user_id = $2 AND action = $3 AND due_at <= $4")
+            .bind(reminder.server_id as i64)
+            .bind(reminder.user_id as i64)
+            .bind(reminder.action.as_str())
+            .bind(reminder.due_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// The body of transfer_paws, for callers that need more done in the same transaction.
+// Nothing is committed, and the caller has to roll back on insufficient funds.
+async fn transfer_in(transaction: &mut Transaction<'_, Postgres>, transfer: &Transfer) -> Result<TransferResult, Error> {
+    let server_id = transfer.server_id as i64;
+    let amount = i64::try_from(transfer.amount).map_err(|_| "That's more paws than can be counted")?;
+    let mut participants: Vec<i64> = [transfer.source_id, transfer.target_id]
+        .into_iter()
+        .flatten()
+        .map(|id| id as i64)
+        .collect();
+
+    // Lock in a stable order so two opposing transfers can't deadlock
+    participants.sort_unstable();
+    participants.dedup();
+
+    // Rows have to exist before they can be locked
+    for user_id in &participants {
+        sqlx::query(r#"
+            INSERT INTO "paw-bot"."paw_count" (user_id, server_id, count)
+            VALUES ($1, $2, 0)
+            ON CONFLICT (user_id, server_id) DO NOTHING;
+        "#)
+            .bind(user_id)
+            .bind(server_id)
+            .execute(&mut **transaction)
+            .await?;
+    }
+
+    let locked = sqlx::query_as::<_,(i64, i64)>(r#"
+        SELECT user_id, count FROM "paw-bot"."paw_count"
+        WHERE server_id = $1 AND user_id = ANY($2)
+        ORDER BY user_id
+        FOR UPDATE;
+    "#)
+        .bind(server_id)
+        .bind(&participants)
+        .fetch_all(&mut **transaction)
+        .await?;
+
+    if let Some(source_id) = transfer.source_id {
+        let available = locked.iter()
+            .find(|(user_id, _)| *user_id == source_id as i64)
+            .map(|(_, count)| *count)
+            .unwrap_or(0);
+
+        if available < amount {
+            return Ok(TransferResult::InsufficientFunds { available: available as u64 });
+        }
+    }
+
+    if let Some(target_id) = transfer.target_id {
+        let held = locked.iter()
+            .find(|(user_id, _)| *user_id == target_id as i64)
+            .map(|(_, count)| *count)
+            .unwrap_or(0);
+
+        if held.checked_add(amount).is_none() {
+            return Err("That would leave a balance with more paws than can be counted".into());
+        }
+    }
+
+    let update_query = r#"
+        UPDATE "paw-bot"."paw_count" SET count = count + $3
+        WHERE user_id = $1 AND server_id = $2
+        RETURNING count;
+    "#;
+
+    let mut source_count = None;
+    if let Some(source_id) = transfer.source_id {
+        let count = sqlx::query_scalar::<_,i64>(update_query)
+            .bind(source_id as i64)
+            .bind(server_id)
+            .bind(-amount)
+            .fetch_one(&mut **transaction)
+            .await?;
+        source_count = Some(count as u64);
+    }
+
+    let mut target_count = None;
+    if let Some(target_id) = transfer.target_id {
+        let count = sqlx::query_scalar::<_,i64>(update_query)
+            .bind(target_id as i64)
+            .bind(server_id)
+            .bind(amount)
+            .fetch_one(&mut **transaction)
+            .await?;
+        target_count = Some(count as u64);
+    }
+
+    sqlx::query(r#"
+        INSERT INTO "paw-bot"."paw_ledger" (server_id, actor_id, source_id, target_id, amount, reason)
+        VALUES ($1, $2, $3, $4, $5, $6);
+    "#)
+        .bind(server_id)
+        .bind(transfer.actor_id as i64)
+        .bind(transfer.source_id.map(|id| id as i64))
+        .bind(transfer.target_id.map(|id| id as i64))
+        .bind(amount)
+        .bind(transfer.reason.as_str())
+        .execute(&mut **transaction)
+        .await?;
+
+    Ok(TransferResult::Completed { source_count, target_count })
+}
+
+// The body of upsert_server_settings, for imports that write settings along with
+// everything else. Nothing is committed.
+async fn upsert_settings_in(transaction: &mut Transaction<'_, Postgres>, server_id: u64, settings: &ServerSettings) -> Result<(), Error> {
+    let query = r#"
+        INSERT INTO "paw-bot"."server_settings" (
+            server_id, steal_interval, gamble_interval, steal_chance, gamble_chance,
+            gamble_critical_chance, gamble_partial_chance,
+            steal_critical_chance, steal_partial_chance, steal_caught_chance, ranking_mode,
+            daily_reward, daily_streak_bonus, daily_streak_cap, daily_grace, daily_reset, timezone,
+            duration_style, gamble_min_stake, gamble_max_stake, gamble_max_stake_percent, gamble_payout_percent
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
+        ON CONFLICT (server_id)
+        DO UPDATE SET steal_interval = $2, gamble_interval = $3, steal_chance = $4, gamble_chance = $5,
+            gamble_critical_chance = $6, gamble_partial_chance = $7,
+            steal_critical_chance = $8, steal_partial_chance = $9, steal_caught_chance = $10, ranking_mode = $11,
+            daily_reward = $12, daily_streak_bonus = $13, daily_streak_cap = $14, daily_grace = $15,
+            daily_reset = $16, timezone = $17, duration_style = $18,
+            gamble_min_stake = $19, gamble_max_stake = $20, gamble_max_stake_percent = $21, gamble_payout_percent = $22;
+    "#;
+
+    sqlx::query(query)
+        .bind(server_id as i64)
+        .bind(settings.steal_interval.duration())
+        .bind(settings.gamble_interval.duration())
+        .bind(settings.steal_chance)
+        .bind(settings.gamble_chance)
+        .bind(settings.gamble_critical_chance)
+        .bind(settings.gamble_partial_chance)
+        .bind(settings.steal_critical_chance)
+        .bind(settings.steal_partial_chance)
+        .bind(settings.steal_caught_chance)
+        .bind(settings.ranking_mode.as_str())
+        .bind(settings.daily_reward)
+        .bind(settings.daily_streak_bonus)
+        .bind(settings.daily_streak_cap)
+        .bind(settings.daily_grace.duration())
+        .bind(settings.daily_reset.as_str())
+        .bind(settings.timezone.name())
+        .bind(settings.duration_style.as_str())
+        .bind(settings.gamble_min_stake)
+        .bind(settings.gamble_max_stake)
+        .bind(settings.gamble_max_stake_percent)
+        .bind(settings.gamble_payout_percent)
+        .execute(&mut **transaction)
+        .await?;
+
+    Ok(())
+}
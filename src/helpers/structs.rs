@@ -1,24 +1,893 @@
-use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
 use sqlx::prelude::FromRow;
+use super::durations::DurationStyle;
 use super::types::MyDuration;
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
 pub struct ServerSettings {
     pub steal_interval: MyDuration,
     pub gamble_interval: MyDuration,
-    pub gamble_chance: i8,
-    pub steal_chance: i8
+    pub gamble_chance: i32,
+    pub steal_chance: i32,
+    pub gamble_critical_chance: i32,
+    pub gamble_partial_chance: i32,
+    pub steal_critical_chance: i32,
+    pub steal_partial_chance: i32,
+    pub steal_caught_chance: i32,
+    pub ranking_mode: RankingMode,
+    pub daily_reward: i32,
+    pub daily_streak_bonus: i32,
+    pub daily_streak_cap: i32,
+    pub daily_grace: MyDuration,
+    pub daily_reset: DailyReset,
+    pub timezone: Tz,
+    pub duration_style: DurationStyle,
+    pub gamble_min_stake: i32,
+    pub gamble_max_stake: i32,
+    pub gamble_max_stake_percent: Option<i32>,
+    pub gamble_payout_percent: Option<i32>
 }
 
-#[derive(Debug, FromRow)]
+// Used by every server that hasn't changed a setting
+impl Default for ServerSettings {
+    fn default() -> Self {
+        ServerSettings {
+            steal_interval: MyDuration(Duration::minutes(1)), // as short as it goes
+            gamble_interval: MyDuration(Duration::minutes(10)),
+            gamble_chance: 50,
+            steal_chance: 50,
+            gamble_critical_chance: 0,
+            gamble_partial_chance: 0,
+            steal_critical_chance: 0,
+            steal_partial_chance: 0,
+            steal_caught_chance: 0,
+            ranking_mode: RankingMode::Standard,
+            daily_reward: 1,
+            daily_streak_bonus: 0, // flat reward, streaks are only for bragging
+            daily_streak_cap: 7,
+            daily_grace: MyDuration(Duration::days(1)),
+            daily_reset: DailyReset::Rolling,
+            timezone: Tz::UTC,
+            duration_style: DurationStyle::Compound,
+            gamble_min_stake: 1,
+            gamble_max_stake: 10,
+            gamble_max_stake_percent: None,
+            gamble_payout_percent: None // even money
+        }
+    }
+}
+
+// How tied farmers push down the ranks below them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankingMode {
+    // 1, 2, 2, 4
+    Standard,
+    // 1, 2, 2, 3
+    Dense
+}
+
+impl RankingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankingMode::Standard => "standard",
+            RankingMode::Dense => "dense"
+        }
+    }
+}
+
+impl std::str::FromStr for RankingMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "standard" => Ok(RankingMode::Standard),
+            "dense" => Ok(RankingMode::Dense),
+            _ => Err(format!("Unknown ranking mode \"{}\", use standard or dense", value))
+        }
+    }
+}
+
+// When the next daily opens up after a claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DailyReset {
+    // A day after the last claim
+    Rolling,
+    // At midnight in the server's timezone
+    Calendar
+}
+
+impl DailyReset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DailyReset::Rolling => "rolling",
+            DailyReset::Calendar => "calendar"
+        }
+    }
+}
+
+impl std::str::FromStr for DailyReset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "rolling" => Ok(DailyReset::Rolling),
+            "calendar" => Ok(DailyReset::Calendar),
+            _ => Err(format!("Unknown daily reset \"{}\", use rolling or calendar", value))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, FromRow)]
 pub struct UserLimits {
-    pub last_steal: NaiveDateTime,
-    pub last_daily: NaiveDateTime,
-    pub last_gamble: NaiveDateTime
+    pub last_steal: Option<DateTime<Utc>>,
+    pub last_daily: Option<DateTime<Utc>>,
+    pub last_gamble: Option<DateTime<Utc>>,
+    pub daily_streak: i32,
+    pub best_daily_streak: i32
+}
+
+// Actions that can only be used once in a while. Dailies keep a streak as well,
+// so they are read through get_daily_streak instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cooldown {
+    Steal,
+    Gamble
+}
+
+impl Cooldown {
+    // Column in user_limits holding the last time the action was used
+    pub fn column(&self) -> &'static str {
+        match self {
+            Cooldown::Steal => "last_steal",
+            Cooldown::Gamble => "last_gamble"
+        }
+    }
+
+    pub fn interval(&self, settings: &ServerSettings) -> Duration {
+        match self {
+            Cooldown::Steal => settings.steal_interval.duration(),
+            Cooldown::Gamble => settings.gamble_interval.duration()
+        }
+    }
+
+    // When the action can be used again, None if it never has been
+    pub fn available_at(&self, settings: &ServerSettings, last_used: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        last_used.map(|last_used| last_used + self.interval(settings))
+    }
+}
+
+impl UserLimits {
+    pub fn last_used(&self, cooldown: Cooldown) -> Option<DateTime<Utc>> {
+        match cooldown {
+            Cooldown::Steal => self.last_steal,
+            Cooldown::Gamble => self.last_gamble
+        }
+    }
+
+    pub fn set_last_used(&mut self, cooldown: Cooldown, time: DateTime<Utc>) {
+        match cooldown {
+            Cooldown::Steal => self.last_steal = Some(time),
+            Cooldown::Gamble => self.last_gamble = Some(time)
+        }
+    }
+}
+
+// A member's run of daily claims that each came before the previous one expired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DailyStreak {
+    pub last_claimed: Option<DateTime<Utc>>,
+    pub current: u32,
+    pub best: u32
+}
+
+// A daily about to be paid out. It only goes through while last_daily is still
+// previous, so two claims racing each other can't both be paid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyClaim {
+    pub user_id: u64,
+    pub server_id: u64,
+    pub previous: Option<DateTime<Utc>>,
+    pub claimed_at: DateTime<Utc>,
+    pub streak: u32,
+    pub best: u32,
+    pub reward: u64
 }
 
-#[derive(FromRow)]
+impl DailyClaim {
+    // The reward comes from the house
+    pub fn transfer(&self) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: self.user_id,
+            source_id: None,
+            target_id: Some(self.user_id),
+            amount: self.reward,
+            reason: LedgerReason::Daily
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
 pub struct PawCount {
     pub count: i64,
     pub user_id: i64,
-}
\ No newline at end of file
+}
+
+// Enough of a Discord user to show them on a leaderboard
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct UserProfile {
+    pub user_id: i64,
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub updated_at: DateTime<Utc>
+}
+
+// Why paws moved, stored as text in the ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum LedgerReason {
+    #[name = "daily"]
+    Daily,
+    #[name = "give"]
+    Give,
+    #[name = "steal"]
+    Steal,
+    #[name = "steal_failed"]
+    StealFailed,
+    #[name = "steal_caught"]
+    StealCaught,
+    #[name = "gamble_win"]
+    GambleWin,
+    // Only in entries from before stakes were held while a game is played
+    #[name = "gamble_loss"]
+    GambleLoss,
+    #[name = "gamble_stake"]
+    GambleStake,
+    #[name = "gamble_refund"]
+    GambleRefund,
+    #[name = "shop_purchase"]
+    ShopPurchase,
+    #[name = "role_purchase"]
+    RolePurchase,
+    #[name = "duel_escrow"]
+    DuelEscrow,
+    #[name = "duel_refund"]
+    DuelRefund,
+    #[name = "duel_win"]
+    DuelWin,
+    #[name = "admin_grant"]
+    AdminGrant,
+    #[name = "admin_revoke"]
+    AdminRevoke,
+    #[name = "admin_set"]
+    AdminSet,
+    #[name = "admin_transfer"]
+    AdminTransfer,
+    #[name = "admin_reset"]
+    AdminReset,
+    #[name = "import"]
+    Import
+}
+
+impl LedgerReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerReason::Daily => "daily",
+            LedgerReason::Give => "give",
+            LedgerReason::Steal => "steal",
+            LedgerReason::StealFailed => "steal_failed",
+            LedgerReason::StealCaught => "steal_caught",
+            LedgerReason::GambleWin => "gamble_win",
+            LedgerReason::GambleLoss => "gamble_loss",
+            LedgerReason::GambleStake => "gamble_stake",
+            LedgerReason::GambleRefund => "gamble_refund",
+            LedgerReason::ShopPurchase => "shop_purchase",
+            LedgerReason::RolePurchase => "role_purchase",
+            LedgerReason::DuelEscrow => "duel_escrow",
+            LedgerReason::DuelRefund => "duel_refund",
+            LedgerReason::DuelWin => "duel_win",
+            LedgerReason::AdminGrant => "admin_grant",
+            LedgerReason::AdminRevoke => "admin_revoke",
+            LedgerReason::AdminSet => "admin_set",
+            LedgerReason::AdminTransfer => "admin_transfer",
+            LedgerReason::AdminReset => "admin_reset",
+            LedgerReason::Import => "import"
+        }
+    }
+}
+
+impl std::str::FromStr for LedgerReason {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "daily" => Ok(LedgerReason::Daily),
+            "give" => Ok(LedgerReason::Give),
+            "steal" => Ok(LedgerReason::Steal),
+            "steal_failed" => Ok(LedgerReason::StealFailed),
+            "steal_caught" => Ok(LedgerReason::StealCaught),
+            "gamble_win" => Ok(LedgerReason::GambleWin),
+            "gamble_loss" => Ok(LedgerReason::GambleLoss),
+            "gamble_stake" => Ok(LedgerReason::GambleStake),
+            "gamble_refund" => Ok(LedgerReason::GambleRefund),
+            "shop_purchase" => Ok(LedgerReason::ShopPurchase),
+            "role_purchase" => Ok(LedgerReason::RolePurchase),
+            "duel_escrow" => Ok(LedgerReason::DuelEscrow),
+            "duel_refund" => Ok(LedgerReason::DuelRefund),
+            "duel_win" => Ok(LedgerReason::DuelWin),
+            "admin_grant" => Ok(LedgerReason::AdminGrant),
+            "admin_revoke" => Ok(LedgerReason::AdminRevoke),
+            "admin_set" => Ok(LedgerReason::AdminSet),
+            "admin_transfer" => Ok(LedgerReason::AdminTransfer),
+            "admin_reset" => Ok(LedgerReason::AdminReset),
+            "import" => Ok(LedgerReason::Import),
+            _ => Err(format!("Unknown ledger reason \"{}\"", value))
+        }
+    }
+}
+
+// A transfer as it was recorded in the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub server_id: u64,
+    pub actor_id: u64,
+    pub source_id: Option<u64>,
+    pub target_id: Option<u64>,
+    pub amount: u64,
+    pub reason: LedgerReason,
+    pub created_at: DateTime<Utc>
+}
+
+// Narrows down the ledger of a server, everything is included when left empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LedgerFilter {
+    // Only transfers that paid or took paws from this user
+    pub user_id: Option<u64>,
+    pub reason: Option<LedgerReason>
+}
+
+impl LedgerFilter {
+    pub fn matches(&self, entry: &LedgerEntry) -> bool {
+        let user = self.user_id.is_none_or(|user_id| entry.source_id == Some(user_id) || entry.target_id == Some(user_id));
+        let reason = self.reason.is_none_or(|reason| entry.reason == reason);
+
+        user && reason
+    }
+}
+
+// A movement of paws between two users. A missing source or target is the house,
+// so paws won from or lost to the bot are still recorded.
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub server_id: u64,
+    pub actor_id: u64,
+    pub source_id: Option<u64>,
+    pub target_id: Option<u64>,
+    pub amount: u64,
+    pub reason: LedgerReason
+}
+
+impl Transfer {
+    // Takes a balance from one count to another through the house, None if it's already there
+    pub fn set_balance(server_id: u64, user_id: u64, actor_id: u64, from: u64, to: u64, reason: LedgerReason) -> Option<Transfer> {
+        Some(Transfer {
+            server_id,
+            actor_id,
+            source_id: if to < from {Some(user_id)} else {None},
+            target_id: if to > from {Some(user_id)} else {None},
+            amount: from.abs_diff(to),
+            reason
+        }).filter(|_| from != to)
+    }
+}
+
+// Most paws a balance can hold, the largest count the databases can store
+pub const MAX_PAWS: u64 = i64::MAX as u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferResult {
+    // New balances of the source and target, if they are users
+    Completed { source_count: Option<u64>, target_count: Option<u64> },
+    // The source held fewer paws than the transfer amount, nothing was moved
+    InsufficientFunds { available: u64 }
+}
+
+// What a shop item does for whoever holds it. Items are used up automatically
+// by the command they affect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ItemEffect {
+    // Blocks the next steal against the holder
+    #[name = "steal_shield"]
+    StealShield,
+    // Moves strength percent from failure to success on the next gamble
+    #[name = "gamble_charm"]
+    GambleCharm
+}
+
+impl ItemEffect {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemEffect::StealShield => "steal_shield",
+            ItemEffect::GambleCharm => "gamble_charm"
+        }
+    }
+}
+
+impl std::str::FromStr for ItemEffect {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "steal_shield" => Ok(ItemEffect::StealShield),
+            "gamble_charm" => Ok(ItemEffect::GambleCharm),
+            _ => Err(format!("Unknown item effect \"{}\"", value))
+        }
+    }
+}
+
+// Something a server sells, names are unique within a server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShopItem {
+    pub id: i64,
+    pub server_id: u64,
+    pub name: String,
+    pub description: String,
+    pub price: u64,
+    pub effect: ItemEffect,
+    pub strength: i32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryItem {
+    pub item: ShopItem,
+    pub quantity: u64
+}
+
+// A server role members can buy, for good or rented for a number of days
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleListing {
+    pub server_id: u64,
+    pub role_id: u64,
+    pub price: u64,
+    pub duration_days: Option<i32>
+}
+
+impl RoleListing {
+    // When a rental bought now runs out. Buying again while it is running adds to it.
+    pub fn rental_end(&self, current: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let days = self.duration_days?;
+        let start = current.filter(|current| *current > now).unwrap_or(now);
+
+        Some(start + Duration::days(days as i64))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleRental {
+    pub server_id: u64,
+    pub user_id: u64,
+    pub role_id: u64,
+    pub expires_at: DateTime<Utc>
+}
+
+// Actions members can be reminded about once they are ready again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, poise::ChoiceParameter)]
+pub enum ReminderAction {
+    #[name = "daily"]
+    Daily,
+    #[name = "gamble"]
+    Gamble,
+    #[name = "steal"]
+    Steal
+}
+
+impl ReminderAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderAction::Daily => "daily",
+            ReminderAction::Gamble => "gamble",
+            ReminderAction::Steal => "steal"
+        }
+    }
+
+    // The plain cooldown behind the action, dailies have their own schedule
+    pub fn cooldown(&self) -> Option<Cooldown> {
+        match self {
+            ReminderAction::Daily => None,
+            ReminderAction::Gamble => Some(Cooldown::Gamble),
+            ReminderAction::Steal => Some(Cooldown::Steal)
+        }
+    }
+}
+
+impl std::str::FromStr for ReminderAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "daily" => Ok(ReminderAction::Daily),
+            "gamble" => Ok(ReminderAction::Gamble),
+            "steal" => Ok(ReminderAction::Steal),
+            _ => Err(format!("Unknown reminder action \"{}\"", value))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ReminderDelivery {
+    // A direct message
+    #[name = "dm"]
+    Dm,
+    // A ping in the channel the reminder was set up in
+    #[name = "channel"]
+    Channel
+}
+
+impl ReminderDelivery {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderDelivery::Dm => "dm",
+            ReminderDelivery::Channel => "channel"
+        }
+    }
+}
+
+impl std::str::FromStr for ReminderDelivery {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "dm" => Ok(ReminderDelivery::Dm),
+            "channel" => Ok(ReminderDelivery::Channel),
+            _ => Err(format!("Unknown reminder delivery \"{}\"", value))
+        }
+    }
+}
+
+// A member who opted in to hear about an action, due_at is set while a reminder is waiting to go out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub server_id: u64,
+    pub user_id: u64,
+    pub action: ReminderAction,
+    pub delivery: ReminderDelivery,
+    pub channel_id: Option<u64>,
+    pub due_at: Option<DateTime<Utc>>
+}
+
+// Everything that can be played for paws, stored as text with each result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Game {
+    Gamble,
+    Coinflip,
+    Dice,
+    Slots,
+    Blackjack
+}
+
+impl Game {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Game::Gamble => "gamble",
+            Game::Coinflip => "coinflip",
+            Game::Dice => "dice",
+            Game::Slots => "slots",
+            Game::Blackjack => "blackjack"
+        }
+    }
+}
+
+impl std::str::FromStr for Game {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "gamble" => Ok(Game::Gamble),
+            "coinflip" => Ok(Game::Coinflip),
+            "dice" => Ok(Game::Dice),
+            "slots" => Ok(Game::Slots),
+            "blackjack" => Ok(Game::Blackjack),
+            _ => Err(format!("Unknown game \"{}\"", value))
+        }
+    }
+}
+
+// A stake about to be held by the house for a game. It only goes through while
+// the gamble cooldown was last used at previous, so two games started at once
+// can't both be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameStake {
+    pub server_id: u64,
+    pub user_id: u64,
+    pub stake: u64,
+    pub previous: Option<DateTime<Utc>>,
+    pub placed_at: DateTime<Utc>
+}
+
+impl GameStake {
+    // The house holds the stake until the game is settled
+    pub fn transfer(&self) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: self.user_id,
+            source_id: Some(self.user_id),
+            target_id: None,
+            amount: self.stake,
+            reason: LedgerReason::GambleStake
+        }
+    }
+
+    pub fn open_game(&self) -> OpenGame {
+        OpenGame { server_id: self.server_id, user_id: self.user_id, stake: self.stake, placed_at: self.placed_at }
+    }
+}
+
+// A game whose stake the house is holding until it's settled or called off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenGame {
+    pub server_id: u64,
+    pub user_id: u64,
+    pub stake: u64,
+    pub placed_at: DateTime<Utc>
+}
+
+impl OpenGame {
+    // Gives the player's stake back
+    pub fn refund(&self) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: self.user_id,
+            source_id: None,
+            target_id: Some(self.user_id),
+            amount: self.stake,
+            reason: LedgerReason::GambleRefund
+        }
+    }
+}
+
+// A finished game. The payout is what the player won (positive) or lost (negative)
+// on top of the stake the house has been holding, so never less than -stake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub server_id: u64,
+    pub user_id: u64,
+    pub game: Game,
+    pub stake: u64,
+    pub payout: i64,
+    pub played_at: DateTime<Utc>
+}
+
+impl GameResult {
+    // Wins pay the stake back with the winnings and pushes just the stake, while
+    // lost stakes stay with the house
+    pub fn transfer(&self) -> Option<Transfer> {
+        let returned = self.stake.checked_add_signed(self.payout).unwrap_or(0);
+
+        Some(Transfer {
+            server_id: self.server_id,
+            actor_id: self.user_id,
+            source_id: None,
+            target_id: Some(self.user_id),
+            amount: returned,
+            reason: if self.payout > 0 {LedgerReason::GambleWin} else {LedgerReason::GambleRefund}
+        }).filter(|_| returned > 0)
+    }
+}
+
+// Everything played of one game on a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameStats {
+    pub game: Game,
+    pub played: u64,
+    pub staked: u64,
+    // What players won between them, negative when the house is ahead
+    pub payout: i64
+}
+
+impl GameStats {
+    // Share of all stakes the house kept, in percent
+    pub fn house_edge(&self) -> f64 {
+        match self.staked {
+            0 => 0.0,
+            staked => -self.payout as f64 * 100.0 / staked as f64
+        }
+    }
+}
+
+// A duel waiting for its target to answer. The house holds the challenger's stake
+// until it is fought, declined or runs out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duel {
+    pub id: i64,
+    pub server_id: u64,
+    pub challenger_id: u64,
+    pub target_id: u64,
+    pub stake: u64,
+    pub expires_at: DateTime<Utc>
+}
+
+impl Duel {
+    // Puts the challenger's stake in escrow
+    pub fn escrow(&self) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: self.challenger_id,
+            source_id: Some(self.challenger_id),
+            target_id: None,
+            amount: self.stake,
+            reason: LedgerReason::DuelEscrow
+        }
+    }
+
+    // Gives the challenger's stake back
+    pub fn refund(&self) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: self.challenger_id,
+            source_id: None,
+            target_id: Some(self.challenger_id),
+            amount: self.stake,
+            reason: LedgerReason::DuelRefund
+        }
+    }
+
+    // Puts the target's stake in escrow to match the challenger's
+    pub fn accept(&self) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: self.target_id,
+            source_id: Some(self.target_id),
+            target_id: None,
+            amount: self.stake,
+            reason: LedgerReason::DuelEscrow
+        }
+    }
+
+    // Both stakes go to the winner
+    pub fn payout(&self, winner_id: u64) -> Transfer {
+        Transfer {
+            server_id: self.server_id,
+            actor_id: winner_id,
+            source_id: None,
+            target_id: Some(winner_id),
+            amount: self.stake * 2,
+            reason: LedgerReason::DuelWin
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuelResult {
+    // Balances once the winner was paid
+    Fought { winner_count: u64, loser_count: u64 },
+    // The target can't match the stake, so the duel stays open
+    InsufficientFunds { available: u64 },
+    // Already fought, declined or run out
+    Closed
+}
+
+// Corrections moderators can make with /paw admin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminAction {
+    Grant,
+    Revoke,
+    Set,
+    Transfer,
+    Reset,
+    Import
+}
+
+impl AdminAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminAction::Grant => "grant",
+            AdminAction::Revoke => "revoke",
+            AdminAction::Set => "set",
+            AdminAction::Transfer => "transfer",
+            AdminAction::Reset => "reset",
+            AdminAction::Import => "import"
+        }
+    }
+}
+
+impl std::str::FromStr for AdminAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "grant" => Ok(AdminAction::Grant),
+            "revoke" => Ok(AdminAction::Revoke),
+            "set" => Ok(AdminAction::Set),
+            "transfer" => Ok(AdminAction::Transfer),
+            "reset" => Ok(AdminAction::Reset),
+            "import" => Ok(AdminAction::Import),
+            _ => Err(format!("Unknown admin action \"{}\"", value))
+        }
+    }
+}
+
+// Recorded as the actor for changes made from the command line, where there's
+// no Discord account to name
+pub const OPERATOR_ID: u64 = 0;
+
+// Who used /paw admin for what. The id is assigned by the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub server_id: u64,
+    pub actor_id: u64,
+    pub action: AdminAction,
+    pub target_id: Option<u64>,
+    pub amount: Option<u64>,
+    // What changed, in words, and why if the moderator said
+    pub details: String,
+    pub created_at: DateTime<Utc>
+}
+
+// What a guild reset took back, and the snapshot of balances from before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EconomyReset {
+    pub snapshot_id: i64,
+    pub farmers: u64,
+    pub paws: u64
+}
+
+// What an import does with the rows a server already has
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ImportMode {
+    // Members in the import are overwritten, everyone else is kept
+    #[name = "merge"]
+    Merge,
+    // The server ends up with exactly what was imported
+    #[name = "replace"]
+    Replace
+}
+
+impl ImportMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportMode::Merge => "merge",
+            ImportMode::Replace => "replace"
+        }
+    }
+}
+
+impl std::str::FromStr for ImportMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "merge" => Ok(ImportMode::Merge),
+            "replace" => Ok(ImportMode::Replace),
+            _ => Err(format!("Unknown import mode \"{}\", use merge or replace", value))
+        }
+    }
+}
+
+// A server's balances, limits and settings, as exported and imported. Settings
+// are None when the server uses the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EconomyData {
+    pub balances: Vec<(u64, u64)>,
+    pub limits: Vec<(u64, UserLimits)>,
+    pub settings: Option<ServerSettings>
+}
+
+impl EconomyData {
+    // What takes the server's current balances to the imported ones, by member.
+    // Replacing also empties the balances of everyone missing from the import.
+    pub fn import_transfers(&self, server_id: u64, actor_id: u64, current: &[(u64, u64)], mode: ImportMode) -> Vec<Transfer> {
+        let mut balances: BTreeMap<u64, (u64, u64)> = current.iter()
+            .map(|(user_id, count)| (*user_id, (*count, if mode == ImportMode::Replace {0} else {*count})))
+            .collect();
+        for (user_id, count) in &self.balances {
+            balances.entry(*user_id).or_insert((0, 0)).1 = *count;
+        }
+
+        balances.into_iter()
+            .filter_map(|(user_id, (from, to))| Transfer::set_balance(server_id, user_id, actor_id, from, to, LedgerReason::Import))
+            .collect()
+    }
+}
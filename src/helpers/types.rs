@@ -1,10 +1,10 @@
 use chrono::Duration;
 use sqlx::prelude::FromRow;
 use sqlx::{Decode, Type, Postgres};
-use sqlx::postgres::{PgValueRef, PgTypeInfo};
+use sqlx::postgres::{PgValueRef, PgTypeInfo, PgValueFormat};
+use sqlx::postgres::types::PgInterval;
 
-#[derive(Debug, FromRow)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromRow)]
 pub struct MyDuration(pub Duration);
 
 impl MyDuration {
@@ -15,8 +15,17 @@ impl MyDuration {
 
 impl<'r> Decode<'r, Postgres> for MyDuration {
     fn decode(value: PgValueRef<'r>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let interval_str = value.as_str()?;
-        let duration = parse_postgres_interval(interval_str)?;
+        // Prepared queries return intervals in binary, plain ones as text
+        let duration = match value.format() {
+            PgValueFormat::Binary => {
+                let interval = PgInterval::decode(value)?;
+                // Months have no fixed length, treat them as 30 days like postgres does
+                Duration::days(interval.months as i64 * 30)
+                    + Duration::days(interval.days as i64)
+                    + Duration::microseconds(interval.microseconds)
+            }
+            PgValueFormat::Text => parse_postgres_interval(value.as_str()?)?
+        };
         Ok(MyDuration(duration))
     }
 }
@@ -34,10 +43,10 @@ fn parse_postgres_interval(interval: &str) -> Result<Duration, Box<dyn std::erro
     for chunk in parts.chunks(2) {
         let value: i64 = chunk[0].parse()?;
         match chunk[1] {
-            "day" | "days" => duration = duration + Duration::days(value),
-            "hour" | "hours" => duration = duration + Duration::hours(value),
-            "minute" | "minutes" => duration = duration + Duration::minutes(value),
-            "second" | "seconds" => duration = duration + Duration::seconds(value),
+            "day" | "days" => duration += Duration::days(value),
+            "hour" | "hours" => duration += Duration::hours(value),
+            "minute" | "minutes" => duration += Duration::minutes(value),
+            "second" | "seconds" => duration += Duration::seconds(value),
             _ => return Err(format!("Unsupported interval part: {}", chunk[1]).into()),
         }
     }
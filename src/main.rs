@@ -1,42 +1,134 @@
 mod helpers;
+mod cli;
 mod commands;
-use helpers::{config, database::{db_create_tables, setup_database}, AppState};
+use clap::Parser;
+use helpers::{config, duels::{expire_duels, refund_open_duels}, open_games::{expire_games, refund_open_games}, profiles::ProfileCache, reminders::send_reminders, rentals::expire_role_rentals, settings::SettingsCache, store::open_store, AppState};
 use poise::{serenity_prelude as serenity, CreateReply};
 use dotenv::dotenv;
-use tracing::log::error;
+use tracing::log::{error, info, warn};
 
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
     dotenv().ok();
-    let config = config::Config::init();
+    let cli = cli::Cli::parse();
+
+    // A broken deploy stops here with everything wrong with it, before connecting anywhere
+    let config = match config::Config::load(cli.config_file()) {
+        Ok(config) => config,
+        Err(problems) => exit_with(format!(
+            "The configuration has {} {}:\n{}",
+            problems.len(), if problems.len() != 1 {"problems"} else {"problem"},
+            problems.iter().map(|problem| format!("  - {}", problem)).collect::<Vec<String>>().join("\n")
+        ))
+    };
+
+    // RUST_LOG still wins, for filtering by module
+    env_logger::Builder::new()
+        .filter_level(config.log_level)
+        .parse_default_env()
+        .init();
+
+    match cli.command() {
+        cli::Command::Run => run_bot(config).await,
+        command => {
+            if let Err(error) = cli::run(command, &config).await {
+                exit_with(error);
+            }
+        }
+    }
+}
+
+fn exit_with(message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+async fn run_bot(config: config::Config) {
+    let Some(token) = config.discord_token.clone() else {
+        exit_with("discord_token isn't set, put it in the config file or set DISCORD_TOKEN");
+    };
+
+    let store = open_store(&config).await
+        .unwrap_or_else(|error| exit_with(format!("Couldn't connect to the database: {}", error)));
+
+    // Bring the schema up to date before anything touches it
+    let applied = store.migrate().await
+        .unwrap_or_else(|error| exit_with(format!("Couldn't migrate the database: {}", error)));
+    info!("Database schema is up to date ({} migrations applied)", applied);
+
+    // Nobody can answer duels from before a restart anymore
+    let refunded = refund_open_duels(store.as_ref()).await
+        .unwrap_or_else(|error| exit_with(format!("Couldn't refund open duels: {}", error)));
+    if refunded > 0 {
+        info!("Refunded {} duels left open by the last run", refunded);
+    }
+
+    // Nor finish games it dealt
+    let refunded = refund_open_games(store.as_ref()).await
+        .unwrap_or_else(|error| exit_with(format!("Couldn't refund open games: {}", error)));
+    if refunded > 0 {
+        info!("Refunded {} games left open by the last run", refunded);
+    }
+
+    let rental_store = store.clone();
+    let reminder_store = store.clone();
+    let duel_store = store.clone();
+    let game_store = store.clone();
     let app_state = AppState { 
         env: config.clone(),
-        db: setup_database(&config).await
+        store,
+        settings: SettingsCache::new(config.defaults.clone()),
+        profiles: ProfileCache::new(chrono::Duration::minutes(config.profile_ttl_minutes), config.persist_profiles)
     };
 
-    // Initialize tables
-    db_create_tables(&app_state.db).await.unwrap();
-
     let intents = serenity::GatewayIntents::non_privileged();
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands: vec![commands::paw()],
+            // Owners skip every check, so they can help out on any server
+            owners: config.owner_ids.iter().map(|id| serenity::UserId::new(*id)).collect(),
+            initialize_owners: false,
+            skip_checks_for_owners: true,
+            // Servers left off the allow-list get told so and nothing else
+            command_check: Some(|ctx| {
+                Box::pin(async move {
+                    let Some(guild_id) = ctx.guild_id() else {
+                        return Ok(true);
+                    };
+                    if ctx.data().env.is_guild_allowed(guild_id.get()) {
+                        return Ok(true);
+                    }
+
+                    ctx.send(CreateReply::default()
+                        .content("Paws aren't farmed on this server")
+                        .ephemeral(true)
+                    ).await?;
+                    Ok(false)
+                })
+            }),
+            // Keeps the names of active farmers fresh without asking Discord
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    if let Err(error) = ctx.data().profiles.remember(ctx.data().store.as_ref(), ctx.author()).await {
+                        warn!("Couldn't update the profile of {}: {:?}", ctx.author().id, error);
+                    }
+                })
+            },
             on_error: |error| {
                 Box::pin(async move {
-                    let ctx = error.ctx();
-                    match ctx {
-                        Some(ctx) => {
-                            let _ = ctx.send(CreateReply::default()
-                                        .content("An error occured. Please try again later")
-                                        .ephemeral(true)
-                                    ).await;
-                        }
-                        None => {
-
-                        }
+                    // Already answered by command_check
+                    if let poise::FrameworkError::CommandCheckFailed { error: None, ctx, .. } = &error {
+                        info!("Refused /{} on server {:?}, it isn't allowed", ctx.command().qualified_name, ctx.guild_id());
+                        return;
+                    }
+
+                    if let Some(ctx) = error.ctx() {
+                        let _ = ctx.send(CreateReply::default()
+                                    .content("An error occured. Please try again later")
+                                    .ephemeral(true)
+                                ).await;
                     }
                     
                     error!("An error has occured: {:?}", error);
@@ -47,13 +139,20 @@ async fn main() {
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                tokio::spawn(expire_role_rentals(ctx.http.clone(), rental_store));
+                tokio::spawn(send_reminders(ctx.http.clone(), reminder_store));
+                tokio::spawn(expire_duels(duel_store));
+                tokio::spawn(expire_games(game_store));
                 Ok(app_state)
             })
         })
         .build();
 
-    let client = serenity::ClientBuilder::new(&config.discord_token, intents)
+    let mut client = serenity::ClientBuilder::new(&token, intents)
         .framework(framework)
-        .await;
-    client.unwrap().start().await.unwrap();
+        .await
+        .unwrap_or_else(|error| exit_with(format!("Couldn't set up the Discord client: {}", error)));
+    if let Err(error) = client.start().await {
+        exit_with(format!("Couldn't connect to Discord: {}", error));
+    }
 }
\ No newline at end of file